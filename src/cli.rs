@@ -3,6 +3,8 @@
 use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 
+use crate::{document::GraphKind, layout::Method};
+
 ///////////////////////////////////////////////////////////////////////////////
 
 #[derive(Parser)]
@@ -24,6 +26,8 @@ pub enum Commands {
     Open(InteractiveArgs),
     Example(ExampleArgs),
     Gui,
+    Render(RenderArgs),
+    Png(PngArgs),
 }
 
 //---------------------------------------------------------------------------//
@@ -32,6 +36,9 @@ pub enum Commands {
 pub struct NewArgs {
     #[arg(short, long)]
     pub path: PathBuf,
+
+    #[arg(short, long, value_enum, default_value_t = GraphKind::Directed)]
+    pub kind: GraphKind,
 }
 
 //---------------------------------------------------------------------------//
@@ -58,4 +65,52 @@ pub struct InteractiveArgs {
     pub path: PathBuf,
 }
 
+//---------------------------------------------------------------------------//
+
+#[derive(Args)]
+pub struct RenderArgs {
+    pub path: PathBuf,
+
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    #[command(flatten)]
+    pub layout: LayoutArgs,
+}
+
+//...........................................................................//
+
+#[derive(Args)]
+pub struct PngArgs {
+    pub path: PathBuf,
+
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Pixels per canvas unit
+    #[arg(short, long, default_value_t = 1.0)]
+    pub scale: f32,
+
+    /// Overrides --scale, 96 DPI is a scale of 1
+    #[arg(short, long, conflicts_with = "scale")]
+    pub dpi: Option<f32>,
+
+    #[command(flatten)]
+    pub layout: LayoutArgs,
+}
+
+//...........................................................................//
+
+#[derive(Args)]
+pub struct LayoutArgs {
+    /// Lay the graph out again instead of using its saved positions,
+    /// pinned nodes stay put
+    #[arg(short, long, value_enum)]
+    pub layout: Option<Method>,
+
+    /// Centre of the radial layout
+    #[arg(long)]
+    pub root: Option<String>,
+}
+
 ///////////////////////////////////////////////////////////////////////////////
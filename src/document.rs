@@ -0,0 +1,476 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{Error, Result};
+use cs_240_library::data_structures::graphs::{
+    directed_graph::DirectedGraph, undirected_graph::UndirectedGraph,
+    weighted_graph::WeightedGraph, IDefiniteGraph, IGraph,
+};
+use egui::Pos2;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{graphs, gui::pages::PanZoom, layout::Graphic};
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Latest document format version
+///
+/// Files without a version are bare serialized graphs, optionally with a
+/// `layout` section, and get migrated on load
+pub const VERSION: u32 = 1;
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    clap::ValueEnum,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphKind {
+    Directed,
+    Undirected,
+    #[default]
+    Weighted,
+}
+
+//---------------------------------------------------------------------------//
+
+/// Graph types that can be stored in a document
+pub trait GraphFile: Serialize + DeserializeOwned {
+    const KIND: GraphKind;
+}
+
+impl GraphFile for DirectedGraph<String> {
+    const KIND: GraphKind = GraphKind::Directed;
+}
+
+impl GraphFile for UndirectedGraph<String> {
+    const KIND: GraphKind = GraphKind::Undirected;
+}
+
+impl GraphFile for WeightedGraph<String, i32> {
+    const KIND: GraphKind = GraphKind::Weighted;
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// A graph file
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct Document<G> {
+    pub version: u32,
+    pub kind: GraphKind,
+
+    #[serde(flatten)]
+    pub info: Info,
+
+    pub graph: G,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<Layout>,
+}
+
+//---------------------------------------------------------------------------//
+
+/// Free-form metadata, not used by any of the algorithms
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Info {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub title: String,
+
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub nodes: BTreeMap<String, Attributes>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub edges: Vec<EdgeAttributes>,
+}
+
+//...........................................................................//
+
+pub type Attributes = BTreeMap<String, serde_yaml::Value>;
+
+//...........................................................................//
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct EdgeAttributes {
+    pub from: String,
+    pub to: String,
+
+    #[serde(flatten)]
+    pub attributes: Attributes,
+}
+
+//---------------------------------------------------------------------------//
+
+/// Saved node positions and viewport
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct Layout {
+    #[serde(default)]
+    pub nodes: BTreeMap<String, Pos2>,
+
+    /// Nodes that re-layouts leave alone
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub pinned: BTreeSet<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view: Option<PanZoom>,
+}
+
+//---------------------------------------------------------------------------//
+
+/// Just enough of a file to tell which format it is in
+#[derive(serde::Deserialize)]
+struct Header {
+    version: Option<u32>,
+    kind: Option<GraphKind>,
+}
+
+//...........................................................................//
+
+/// Unversioned files
+#[derive(serde::Deserialize)]
+struct Legacy<G> {
+    #[serde(flatten)]
+    graph: G,
+
+    #[serde(default)]
+    layout: Option<Layout>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+impl<G: GraphFile> Document<G> {
+    pub fn new(graph: G) -> Self {
+        Self {
+            version: VERSION,
+            kind: G::KIND,
+            info: Default::default(),
+            graph,
+            layout: None,
+        }
+    }
+
+    //-----------------------------------------------------------------------//
+
+    /// Reads a document, migrating unversioned files
+    pub fn parse(text: &str) -> Result<Self> {
+        let header: Header = serde_yaml::from_str(text)?;
+
+        match header.version {
+            None => {
+                let legacy: Legacy<G> = serde_yaml::from_str(text)?;
+                Ok(Self {
+                    layout: legacy.layout,
+                    ..Self::new(legacy.graph)
+                })
+            }
+            Some(version) if version > VERSION => Err(Error::msg(format!(
+                "File is format version {}, but only up to {} is supported",
+                version, VERSION
+            ))),
+            Some(_) => {
+                if let Some(kind) = header.kind.filter(|kind| *kind != G::KIND) {
+                    return Err(Error::msg(format!(
+                        "Expected a {:?} graph, found {:?}",
+                        G::KIND,
+                        kind
+                    )));
+                }
+
+                let mut doc: Self = serde_yaml::from_str(text)?;
+                doc.version = VERSION;
+                Ok(doc)
+            }
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+impl<G> Document<G> {
+    /// Saved node positions, empty if the file has no layout
+    pub fn graphic(&self) -> Graphic {
+        self.layout
+            .iter()
+            .flat_map(|layout| layout.nodes.iter())
+            .map(|(node, pos)| (node.clone(), *pos))
+            .collect()
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+impl Layout {
+    /// Positions of the nodes in `graph`, dropping any stale entries
+    pub fn new<G>(
+        graph: &G,
+        graphic: &Graphic,
+        pinned: &BTreeSet<String>,
+        view: Option<PanZoom>,
+    ) -> Self
+    where
+        G: IGraph<String>,
+    {
+        Self {
+            nodes: graphic
+                .iter()
+                .filter(|(node, _)| graph.contains(node))
+                .map(|(node, pos)| (node.clone(), *pos))
+                .collect(),
+            pinned: pinned
+                .iter()
+                .filter(|node| graph.contains(node))
+                .cloned()
+                .collect(),
+            view,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Kind of graph stored in a document
+///
+/// Unversioned files don't say, so they are weighted if they parse as such and
+/// directed otherwise
+pub fn kind(text: &str) -> Result<GraphKind> {
+    let header: Header = serde_yaml::from_str(text)?;
+
+    match (header.version, header.kind) {
+        (_, Some(kind)) => Ok(kind),
+        (Some(_), None) => Err(Error::msg("File is missing its graph kind")),
+        (None, None) => {
+            if serde_yaml::from_str::<Legacy<WeightedGraph<String, i32>>>(text).is_ok() {
+                Ok(GraphKind::Weighted)
+            } else {
+                Ok(GraphKind::Directed)
+            }
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Reads a document of any kind as a weighted graph
+///
+/// Unweighted edges get a weight of 1. The document keeps the kind it was
+/// stored as, so `save_weighted` can write it back the same way
+pub fn load_weighted(text: &str) -> Result<Document<WeightedGraph<String, i32>>> {
+    match kind(text)? {
+        GraphKind::Weighted => Document::parse(text),
+        GraphKind::Directed => Ok(to_weighted(Document::<DirectedGraph<String>>::parse(text)?)),
+        GraphKind::Undirected => Ok(to_weighted(Document::<UndirectedGraph<String>>::parse(
+            text,
+        )?)),
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Writes a weighted document back out as the kind it says it is
+///
+/// Directed and undirected files lose the weights, and each undirected edge
+/// is only written once
+pub fn save_weighted(doc: &Document<WeightedGraph<String, i32>>) -> Result<String> {
+    match doc.kind {
+        GraphKind::Weighted => Ok(serde_yaml::to_string(doc)?),
+        GraphKind::Directed => Ok(serde_yaml::to_string(&with_graph(
+            doc,
+            graphs::to_directed(&doc.graph),
+        ))?),
+        GraphKind::Undirected => Ok(serde_yaml::to_string(&with_graph(
+            doc,
+            graphs::to_undirected(&doc.graph),
+        ))?),
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Just the graph, as `kind` writes it, for editing on its own
+pub fn graph_text(graph: &WeightedGraph<String, i32>, kind: GraphKind) -> Result<String> {
+    match kind {
+        GraphKind::Weighted => Ok(serde_yaml::to_string(graph)?),
+        GraphKind::Directed => Ok(serde_yaml::to_string(&graphs::to_directed(graph))?),
+        GraphKind::Undirected => Ok(serde_yaml::to_string(&graphs::to_undirected(graph))?),
+    }
+}
+
+//...........................................................................//
+
+/// Reads a graph written by `graph_text`
+pub fn parse_graph(text: &str, kind: GraphKind) -> serde_yaml::Result<WeightedGraph<String, i32>> {
+    match kind {
+        GraphKind::Weighted => serde_yaml::from_str(text),
+        GraphKind::Directed => Ok(graphs::to_weighted(&serde_yaml::from_str::<
+            DirectedGraph<String>,
+        >(text)?)),
+        GraphKind::Undirected => Ok(graphs::to_weighted(&serde_yaml::from_str::<
+            UndirectedGraph<String>,
+        >(text)?)),
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+fn to_weighted<G>(doc: Document<G>) -> Document<WeightedGraph<String, i32>>
+where
+    G: IDefiniteGraph<String>,
+{
+    Document {
+        version: doc.version,
+        kind: doc.kind,
+        info: doc.info,
+        graph: graphs::to_weighted(&doc.graph),
+        layout: doc.layout,
+    }
+}
+
+//...........................................................................//
+
+fn with_graph<G, H>(doc: &Document<G>, graph: H) -> Document<H> {
+    Document {
+        version: doc.version,
+        kind: doc.kind,
+        info: doc.info.clone(),
+        graph,
+        layout: doc.layout.clone(),
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use cs_240_library::data_structures::graphs::{IGraphEdgeMut, IGraphMut, IWeightedGraph};
+
+    use super::*;
+
+    //-----------------------------------------------------------------------//
+
+    const LEGACY: &str = "\
+adj:
+  a:
+  - b
+  b: []
+layout:
+  nodes:
+    a:
+      x: 1.0
+      y: 2.0
+";
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn migrates_unversioned_files() {
+        let doc = Document::<DirectedGraph<String>>::parse(LEGACY).unwrap();
+
+        assert_eq!(doc.version, VERSION);
+        assert_eq!(doc.kind, GraphKind::Directed);
+        assert_eq!(doc.graph.get_adj(&"a".to_owned()), ["b"]);
+        assert_eq!(doc.graphic().get("a"), Some(&Pos2::new(1.0, 2.0)));
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn round_trips_through_the_latest_version() {
+        let doc = Document::<DirectedGraph<String>>::parse(LEGACY).unwrap();
+        let text = serde_yaml::to_string(&doc).unwrap();
+        let again = Document::<DirectedGraph<String>>::parse(&text).unwrap();
+
+        assert!(text.starts_with(&format!("version: {}", VERSION)));
+        assert_eq!(again.graph.get_adj(&"a".to_owned()), ["b"]);
+        assert_eq!(again.graphic().get("a"), Some(&Pos2::new(1.0, 2.0)));
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn rejects_newer_versions() {
+        let text = format!(
+            "version: {}\nkind: directed\ngraph:\n  adj: {{}}\n",
+            VERSION + 1
+        );
+        assert!(Document::<DirectedGraph<String>>::parse(&text).is_err());
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn rejects_the_wrong_kind() {
+        let text = "version: 1\nkind: undirected\ngraph:\n  adj: {}\n";
+        assert!(Document::<DirectedGraph<String>>::parse(text).is_err());
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn detects_the_kind_of_versioned_files() {
+        let text = "version: 1\nkind: undirected\ngraph:\n  adj: {}\n";
+        assert_eq!(kind(text).unwrap(), GraphKind::Undirected);
+
+        let text = "version: 1\ngraph:\n  adj: {}\n";
+        assert!(kind(text).is_err());
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn guesses_the_kind_of_unversioned_files() {
+        assert_eq!(kind(LEGACY).unwrap(), GraphKind::Directed);
+
+        let weighted = "adj:\n  a:\n  - - b\n    - 3\n  b: []\n";
+        assert_eq!(kind(weighted).unwrap(), GraphKind::Weighted);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn loads_unweighted_files_with_weight_one() {
+        let doc = load_weighted(LEGACY).unwrap();
+
+        // still the kind the file is stored as
+        assert_eq!(doc.kind, GraphKind::Directed);
+        assert_eq!(
+            doc.graph.get_adj_weighted(&"a".to_owned()),
+            [("b".to_owned(), 1)]
+        );
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn saves_undirected_files_as_undirected() {
+        let mut graph = UndirectedGraph::new();
+        for node in ["a", "b", "c"] {
+            graph.insert_node(node.to_owned());
+        }
+        graph.insert_edge("a".to_owned(), "b".to_owned());
+        graph.insert_edge("c".to_owned(), "b".to_owned());
+        let text = serde_yaml::to_string(&Document::new(graph.clone())).unwrap();
+
+        let saved = save_weighted(&load_weighted(&text).unwrap()).unwrap();
+        let again = Document::<UndirectedGraph<String>>::parse(&saved).unwrap();
+
+        assert_eq!(kind(&saved).unwrap(), GraphKind::Undirected);
+        assert_eq!(
+            graphs::edges(&graphs::to_weighted(&again.graph)),
+            graphs::edges(&graphs::to_weighted(&graph))
+        );
+    }
+
+    //-----------------------------------------------------------------------//
+}
@@ -0,0 +1,375 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+};
+
+use anyhow::{Error, Result};
+use cs_240_library::data_structures::graphs::{
+    directed_graph::DirectedGraph, undirected_graph::UndirectedGraph,
+    weighted_graph::WeightedGraph, IDefiniteGraph, IGraph, IGraphEdgeMut, IGraphEdgeWeightedMut,
+    IGraphMut, IWeightedGraph,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+
+pub type Edge = (String, String, i32);
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Copies any graph into a weighted one, every edge gets a weight of 1
+pub fn to_weighted<G>(graph: &G) -> WeightedGraph<String, i32>
+where
+    G: IDefiniteGraph<String>,
+{
+    let mut edges = vec![];
+
+    for node in graph.get_all() {
+        for adj in graph.get_adj(&node) {
+            edges.push((node.clone(), adj, 1));
+        }
+    }
+
+    build(graph.get_all(), edges)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Copies a weighted graph into a directed one, dropping the weights
+pub fn to_directed(graph: &WeightedGraph<String, i32>) -> DirectedGraph<String> {
+    let mut res = DirectedGraph::new();
+
+    for node in graph.get_all() {
+        res.insert_node(node);
+    }
+    for (from, to, _) in edges(graph) {
+        res.insert_edge(from, to);
+    }
+
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// Copies a weighted graph into an undirected one, dropping the weights
+///
+/// `a→b` and `b→a` become the same edge
+pub fn to_undirected(graph: &WeightedGraph<String, i32>) -> UndirectedGraph<String> {
+    let mut res = UndirectedGraph::new();
+
+    for node in graph.get_all() {
+        res.insert_node(node);
+    }
+    for (from, to, _) in undirected_edges(graph) {
+        res.insert_edge(from, to);
+    }
+
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// All edges of the graph, sorted
+pub fn edges(graph: &WeightedGraph<String, i32>) -> Vec<Edge> {
+    let mut edges = vec![];
+
+    for node in graph.get_all() {
+        for (adj, weight) in graph.get_adj_weighted(&node) {
+            edges.push((node.clone(), adj, weight));
+        }
+    }
+
+    edges.sort();
+    edges
+}
+
+//---------------------------------------------------------------------------//
+
+/// Edges of the graph read as undirected, with only one of `a→b` and `b→a`
+pub fn undirected_edges(graph: &WeightedGraph<String, i32>) -> Vec<Edge> {
+    edges(graph)
+        .into_iter()
+        .filter(|(from, to, _)| from <= to || !graph.get_adj(to).contains(from))
+        .collect()
+}
+
+//---------------------------------------------------------------------------//
+
+pub fn build(nodes: Vec<String>, edges: Vec<Edge>) -> WeightedGraph<String, i32> {
+    let mut graph = WeightedGraph::new();
+
+    for node in nodes {
+        graph.insert_node(node);
+    }
+    for (from, to, weight) in edges {
+        graph.insert_edge_weighted(from, to, weight);
+    }
+
+    graph
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Removes an edge by rebuilding the graph without it
+pub fn remove_edge(graph: &mut WeightedGraph<String, i32>, from: &str, to: &str) {
+    let edges = edges(graph)
+        .into_iter()
+        .filter(|(a, b, _)| !(a == from && b == to))
+        .collect();
+
+    *graph = build(graph.get_all(), edges);
+}
+
+//---------------------------------------------------------------------------//
+
+/// Inserts or replaces an edge
+pub fn set_edge(graph: &mut WeightedGraph<String, i32>, from: &str, to: &str, weight: i32) {
+    remove_edge(graph, from, to);
+    graph.insert_edge_weighted(from.to_owned(), to.to_owned(), weight);
+}
+
+//---------------------------------------------------------------------------//
+
+/// Removes a node along with every edge into or out of it
+pub fn remove_node(graph: &mut WeightedGraph<String, i32>, node: &str) {
+    let nodes = graph.get_all().into_iter().filter(|n| n != node).collect();
+    let edges = edges(graph)
+        .into_iter()
+        .filter(|(a, b, _)| a != node && b != node)
+        .collect();
+
+    *graph = build(nodes, edges);
+}
+
+//---------------------------------------------------------------------------//
+
+/// Renames a node, keeping its edges
+pub fn rename_node(graph: &mut WeightedGraph<String, i32>, old: &str, new: &str) {
+    let rename = |n: String| if n == old { new.to_owned() } else { n };
+
+    let nodes = graph.get_all().into_iter().map(rename).collect();
+    let edges = edges(graph)
+        .into_iter()
+        .map(|(a, b, weight)| (rename(a), rename(b), weight))
+        .collect();
+
+    *graph = build(nodes, edges);
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// A path through the graph
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path {
+    /// From the start to the end, both included
+    pub nodes: Vec<String>,
+    /// Sum of the edge weights along the way
+    pub weight: i64,
+}
+
+//---------------------------------------------------------------------------//
+
+/// Path with the fewest edges, by breadth-first search
+pub fn fewest_edges(graph: &WeightedGraph<String, i32>, from: &str, to: &str) -> Option<Path> {
+    let from = from.to_owned();
+    if !graph.contains(&from) {
+        return None;
+    }
+
+    let mut parents: HashMap<String, (String, i32)> = HashMap::new();
+    let mut queue = VecDeque::from([from.clone()]);
+
+    while let Some(node) = queue.pop_front() {
+        if node == to {
+            break;
+        }
+
+        let mut adj = graph.get_adj_weighted(&node);
+        adj.sort();
+
+        for (adj, weight) in adj {
+            if adj != from && !parents.contains_key(&adj) {
+                parents.insert(adj.clone(), (node.clone(), weight));
+                queue.push_back(adj);
+            }
+        }
+    }
+
+    walk_back(&parents, &from, to)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Path with the lowest total weight, by Dijkstra's algorithm
+///
+/// Fails if the graph has a negative weight, since Dijkstra can't handle them
+pub fn lowest_cost(
+    graph: &WeightedGraph<String, i32>,
+    from: &str,
+    to: &str,
+) -> Result<Option<Path>> {
+    if let Some((a, b, weight)) = edges(graph).into_iter().find(|(_, _, w)| *w < 0) {
+        return Err(Error::msg(format!(
+            "{} -> {} has a negative weight ({})",
+            a, b, weight
+        )));
+    }
+
+    let from = from.to_owned();
+    if !graph.contains(&from) {
+        return Ok(None);
+    }
+
+    let mut dist: HashMap<String, i64> = HashMap::from([(from.clone(), 0)]);
+    let mut parents: HashMap<String, (String, i32)> = HashMap::new();
+    let mut heap = BinaryHeap::from([Reverse((0, from.clone()))]);
+
+    while let Some(Reverse((d, node))) = heap.pop() {
+        if node == to {
+            break;
+        }
+        if d > dist[&node] {
+            continue;
+        }
+
+        for (adj, weight) in graph.get_adj_weighted(&node) {
+            let next = d + i64::from(weight);
+            if dist.get(&adj).map_or(true, |old| next < *old) {
+                dist.insert(adj.clone(), next);
+                parents.insert(adj.clone(), (node.clone(), weight));
+                heap.push(Reverse((next, adj)));
+            }
+        }
+    }
+
+    Ok(walk_back(&parents, &from, to))
+}
+
+//---------------------------------------------------------------------------//
+
+fn walk_back(parents: &HashMap<String, (String, i32)>, from: &str, to: &str) -> Option<Path> {
+    let mut nodes = vec![to.to_owned()];
+    let mut weight = 0;
+
+    while nodes.last().unwrap() != from {
+        let (parent, w) = parents.get(nodes.last().unwrap())?;
+        weight += i64::from(*w);
+        nodes.push(parent.clone());
+    }
+
+    nodes.reverse();
+    Some(Path { nodes, weight })
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //-----------------------------------------------------------------------//
+
+    /// a -> b -> c, a -> c, and d on its own
+    fn sample() -> WeightedGraph<String, i32> {
+        let nodes = ["a", "b", "c", "d"].map(String::from).to_vec();
+        let edges = [("a", "b", 1), ("b", "c", 2), ("a", "c", 5)]
+            .map(|(from, to, weight)| (from.to_owned(), to.to_owned(), weight))
+            .to_vec();
+        build(nodes, edges)
+    }
+
+    //-----------------------------------------------------------------------//
+
+    fn nodes(graph: &WeightedGraph<String, i32>) -> Vec<String> {
+        let mut nodes = graph.get_all();
+        nodes.sort();
+        nodes
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn remove_node_drops_its_edges() {
+        let mut graph = sample();
+        remove_node(&mut graph, "b");
+
+        assert_eq!(nodes(&graph), ["a", "c", "d"]);
+        assert_eq!(edges(&graph), [("a".to_owned(), "c".to_owned(), 5)]);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn rename_node_keeps_its_edges() {
+        let mut graph = sample();
+        rename_node(&mut graph, "b", "x");
+
+        assert_eq!(nodes(&graph), ["a", "c", "d", "x"]);
+        assert_eq!(
+            edges(&graph),
+            [
+                ("a".to_owned(), "c".to_owned(), 5),
+                ("a".to_owned(), "x".to_owned(), 1),
+                ("x".to_owned(), "c".to_owned(), 2),
+            ]
+        );
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn fewest_edges_takes_the_direct_edge() {
+        let path = fewest_edges(&sample(), "a", "c").unwrap();
+
+        assert_eq!(path.nodes, ["a", "c"]);
+        assert_eq!(path.weight, 5);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn lowest_cost_takes_the_cheaper_detour() {
+        let path = lowest_cost(&sample(), "a", "c").unwrap().unwrap();
+
+        assert_eq!(path.nodes, ["a", "b", "c"]);
+        assert_eq!(path.weight, 3);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn paths_to_unreachable_or_missing_nodes() {
+        let graph = sample();
+
+        assert_eq!(fewest_edges(&graph, "a", "d"), None);
+        assert_eq!(fewest_edges(&graph, "c", "a"), None);
+        assert_eq!(fewest_edges(&graph, "x", "a"), None);
+        assert_eq!(lowest_cost(&graph, "a", "d").unwrap(), None);
+        assert_eq!(lowest_cost(&graph, "x", "a").unwrap(), None);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn path_to_itself_is_empty() {
+        let path = lowest_cost(&sample(), "a", "a").unwrap().unwrap();
+
+        assert_eq!(path.nodes, ["a"]);
+        assert_eq!(path.weight, 0);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn lowest_cost_rejects_negative_weights() {
+        let mut graph = sample();
+        set_edge(&mut graph, "b", "c", -1);
+
+        assert!(lowest_cost(&graph, "a", "c").is_err());
+        // breadth-first search doesn't look at weights
+        assert!(fewest_edges(&graph, "a", "c").is_some());
+    }
+
+    //-----------------------------------------------------------------------//
+}
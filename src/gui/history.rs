@@ -0,0 +1,294 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use std::{
+    collections::BTreeSet,
+    time::{Duration, Instant},
+};
+
+use cs_240_library::data_structures::graphs::weighted_graph::WeightedGraph;
+
+use crate::{document::Info, gui::pages::Diagnostic, layout::Graphic};
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Most undo steps kept per project
+const MAX_STATES: usize = 200;
+
+/// Roughly the most memory the undo steps of a project take up, since every
+/// step holds the whole graph
+const MAX_BYTES: usize = 64 * 1024 * 1024;
+
+/// Text edits closer together than this become one undo step
+const COALESCE_WINDOW: Duration = Duration::from_millis(800);
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Everything an undo step puts back
+#[derive(Clone)]
+pub struct Snapshot {
+    pub text: String,
+    pub graph: WeightedGraph<String, i32>,
+    /// Where the nodes are, or are headed if a layout is animating
+    pub graphic: Graphic,
+    pub pinned: BTreeSet<String>,
+    pub info: Info,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Snapshot {
+    /// Estimate of the bytes the state takes up
+    fn size(&self) -> usize {
+        // per entry overhead of the maps and sets
+        const ENTRY: usize = 48;
+
+        // the graph is about as big as the text it was read from
+        2 * self.text.len()
+            + self
+                .graphic
+                .keys()
+                .chain(&self.pinned)
+                .map(|name| name.len() + ENTRY)
+                .sum::<usize>()
+            + self.info.title.len()
+            + self.info.description.len()
+            + ENTRY * (self.info.nodes.len() + self.info.edges.len())
+            + self
+                .diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.message.len() + ENTRY)
+                .sum::<usize>()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// States of a project after each edit, with the current one in the middle
+/// somewhere once something has been undone
+#[derive(Default)]
+pub struct History {
+    states: Vec<Snapshot>,
+    /// Size of each state
+    sizes: Vec<usize>,
+    current: usize,
+    /// Text edits before this get merged into the current state
+    coalesce_until: Option<Instant>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl History {
+    //-------------------------------------------------------------------------//
+
+    /// Forgets everything, starting again from `state`
+    pub fn reset(&mut self, state: Snapshot) {
+        self.sizes = vec![state.size()];
+        self.states = vec![state];
+        self.current = 0;
+        self.coalesce_until = None;
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// Adds the state after an edit, dropping anything that was undone
+    ///
+    /// Typing merges into the last state until there is a pause
+    pub fn push(&mut self, state: Snapshot, text_edit: bool) {
+        let now = Instant::now();
+        let coalesce = text_edit && self.coalesce_until.is_some_and(|until| now < until);
+
+        if coalesce && !self.states.is_empty() {
+            self.sizes[self.current] = state.size();
+            self.states[self.current] = state;
+        } else {
+            self.states.truncate(self.current + 1);
+            self.sizes.truncate(self.current + 1);
+            self.sizes.push(state.size());
+            self.states.push(state);
+        }
+
+        // the oldest steps go first, but the current one always stays
+        while self.states.len() > 1
+            && (self.states.len() > MAX_STATES || self.sizes.iter().sum::<usize>() > MAX_BYTES)
+        {
+            self.states.remove(0);
+            self.sizes.remove(0);
+        }
+        self.current = self.states.len() - 1;
+
+        self.coalesce_until = text_edit.then(|| now + COALESCE_WINDOW);
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// Updates the positions of the current state once a layout finishes
+    pub fn amend_graphic(&mut self, graphic: &Graphic) {
+        if let Some(state) = self.states.get_mut(self.current) {
+            state.graphic = graphic.clone();
+            self.sizes[self.current] = state.size();
+        }
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn can_undo(&self) -> bool {
+        self.current > 0
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn can_redo(&self) -> bool {
+        self.current + 1 < self.states.len()
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// State to go back to
+    pub fn undo(&mut self) -> Option<Snapshot> {
+        if !self.can_undo() {
+            return None;
+        }
+        self.current -= 1;
+        self.coalesce_until = None;
+        Some(self.states[self.current].clone())
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// State to go forward to
+    pub fn redo(&mut self) -> Option<Snapshot> {
+        if !self.can_redo() {
+            return None;
+        }
+        self.current += 1;
+        self.coalesce_until = None;
+        Some(self.states[self.current].clone())
+    }
+
+    //-------------------------------------------------------------------------//
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+
+    use super::*;
+
+    //-----------------------------------------------------------------------//
+
+    fn state(text: &str) -> Snapshot {
+        Snapshot {
+            text: text.to_owned(),
+            graph: WeightedGraph::new(),
+            graphic: Graphic::default(),
+            pinned: BTreeSet::new(),
+            info: Info::default(),
+            diagnostics: vec![],
+        }
+    }
+
+    //-----------------------------------------------------------------------//
+
+    fn history(text: &str) -> History {
+        let mut history = History::default();
+        history.reset(state(text));
+        history
+    }
+
+    //-----------------------------------------------------------------------//
+
+    /// Undoes as far as it goes, returning the texts on the way
+    fn undo_all(history: &mut History) -> Vec<String> {
+        std::iter::from_fn(|| history.undo().map(|state| state.text)).collect()
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn typing_coalesces_into_one_step() {
+        let mut history = history("");
+        history.push(state("a"), true);
+        history.push(state("ab"), true);
+        history.push(state("abc"), true);
+
+        assert_eq!(undo_all(&mut history), [""]);
+        assert_eq!(history.redo().unwrap().text, "abc");
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn other_edits_are_steps_of_their_own() {
+        let mut history = history("");
+        history.push(state("a"), true);
+        history.push(state("b"), false);
+        history.push(state("c"), false);
+        // typing right after another edit still starts a new step
+        history.push(state("cd"), true);
+
+        assert_eq!(undo_all(&mut history), ["c", "b", "a", ""]);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn undo_stops_typing_from_coalescing() {
+        let mut history = history("");
+        history.push(state("a"), true);
+        history.undo();
+        history.push(state("x"), true);
+
+        assert!(!history.can_redo());
+        assert_eq!(undo_all(&mut history), [""]);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn keeps_at_most_max_states() {
+        let mut history = history("0");
+        for i in 1..MAX_STATES + 50 {
+            history.push(state(&i.to_string()), false);
+        }
+
+        let undone = undo_all(&mut history);
+        assert_eq!(undone.len(), MAX_STATES - 1);
+        assert_eq!(undone.last().unwrap(), "50");
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn keeps_at_most_max_bytes() {
+        let big = "x".repeat(MAX_BYTES / 5);
+        let mut history = history(&big);
+        for i in 0..5 {
+            history.push(state(&format!("{}{}", big, i)), false);
+        }
+
+        let states = 1 + undo_all(&mut history).len();
+        assert!(states * state(&big).size() <= MAX_BYTES);
+        assert!(states >= 2);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn amend_graphic_changes_the_current_state() {
+        let mut history = history("");
+        history.push(state("a"), false);
+
+        let graphic: Graphic = [("a".to_owned(), Pos2::new(1.0, 2.0))]
+            .into_iter()
+            .collect();
+        history.amend_graphic(&graphic);
+        history.undo();
+
+        assert_eq!(history.redo().unwrap().graphic, graphic);
+    }
+
+    //-----------------------------------------------------------------------//
+}
@@ -1,88 +1,270 @@
 ///////////////////////////////////////////////////////////////////////////////
 
-use pages::Page;
+use anyhow::{Error, Result};
 use std::path::PathBuf;
 
 use self::{
-    modals::Modal,
+    modals::{ExportImage, Modal, PathInput},
     pages::Project,
-    widgets::{modal_view::modal_view, page_view::page_view, top_bar::top_bar},
+    palette::{undo_shortcuts, PALETTE_SHORTCUT},
+    widgets::{modal_view::modal_view, page_view::page_view, tab_bar::tab_bar, top_bar::top_bar},
 };
 
 ///////////////////////////////////////////////////////////////////////////////
 
+pub mod history;
 pub mod modals;
 pub mod pages;
+pub mod palette;
+pub mod search;
 pub mod widgets;
+pub mod worker;
 
 ///////////////////////////////////////////////////////////////////////////////
 
-#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Default, serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct App {
-    active: Page,
+    /// Open projects, restored with the rest of the app
+    tabs: Vec<Project>,
+    /// Index of the tab being shown
+    current: usize,
     recent: Vec<PathBuf>,
 
+    /// The one project sessions kept before there were tabs
+    #[serde(rename = "active", skip_serializing)]
+    legacy: Page,
+
     #[serde(skip)]
     modals: Vec<Modal>,
 }
 
 //---------------------------------------------------------------------------//
 
+/// What older sessions had open, moved into the tabs when restored
+#[derive(Default, serde::Deserialize)]
+enum Page {
+    #[default]
+    Blank,
+    Project(Box<Project>),
+}
+
+//---------------------------------------------------------------------------//
+
 impl App {
     //-------------------------------------------------------------------------//
 
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         match cc.storage {
-            Some(storage) => eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default(),
+            Some(storage) => {
+                let mut app: Self = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+                app.migrate();
+                for project in &mut app.tabs {
+                    // canvas edits would overwrite text that doesn't parse
+                    project.recheck();
+                    project.reset_history();
+                }
+                app
+            }
             _ => Default::default(),
         }
     }
 
     //-------------------------------------------------------------------------//
 
+    /// Opens the project of a session from before tabs in a tab of its own
+    fn migrate(&mut self) {
+        if let Page::Project(project) = std::mem::take(&mut self.legacy) {
+            self.tabs.push(*project);
+            self.current = self.tabs.len() - 1;
+        }
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn project(&self) -> Option<&Project> {
+        self.tabs.get(self.current)
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn project_mut(&mut self) -> Option<&mut Project> {
+        self.tabs.get_mut(self.current)
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// Opens `project` in a new tab and switches to it
+    fn add_tab(&mut self, project: Project) {
+        self.tabs.push(project);
+        self.current = self.tabs.len() - 1;
+    }
+
+    //-------------------------------------------------------------------------//
+
     pub fn new_graph(&mut self) {
-        self.active = Page::Project(Project::new());
+        self.add_tab(Project::new());
     }
 
     //-------------------------------------------------------------------------//
 
     pub fn find_graph(&mut self) {
-        todo!()
+        self.modals.push(Modal::FindFile(PathInput::default()));
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// Switches to the graph if it is already open
+    pub fn open_graph(&mut self, path: PathBuf) -> Result<()> {
+        match self
+            .tabs
+            .iter()
+            .position(|project| project.path.as_ref() == Some(&path))
+        {
+            Some(index) => self.current = index,
+            None => self.add_tab(Project::open(path.clone())?),
+        }
+        self.add_recent(path);
+        Ok(())
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn save_graph(&mut self) -> Result<()> {
+        match self.project_mut() {
+            Some(project) if project.path.is_some() => project.save(),
+            Some(_) => {
+                self.save_graph_as();
+                Ok(())
+            }
+            None => Err(Error::msg("No project open")),
+        }
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn save_graph_as(&mut self) {
+        if let Some(project) = self.project() {
+            let path = project
+                .path
+                .as_ref()
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or("graph.yaml".to_owned());
+
+            self.modals
+                .push(Modal::SaveFile(PathInput { path, error: None }));
+        }
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn save_graph_to(&mut self, path: PathBuf) -> Result<()> {
+        match self.project_mut() {
+            Some(project) => {
+                project.path = Some(path.clone());
+                project.save()?;
+            }
+            None => return Err(Error::msg("No project open")),
+        }
+        self.add_recent(path);
+        Ok(())
+    }
+
+    //-------------------------------------------------------------------------//
+
+    fn add_recent(&mut self, path: PathBuf) {
+        self.recent.retain(|other| *other != path);
+        self.recent.insert(0, path);
+        self.recent.truncate(10);
     }
 
     //-------------------------------------------------------------------------//
 
-    pub fn open_graph(&mut self) {
-        todo!()
+    pub fn export_image(&mut self) {
+        if let Some(project) = self.project() {
+            let path = project
+                .path
+                .as_ref()
+                .map(|path| path.with_extension("png"))
+                .unwrap_or(PathBuf::from("graph.png"));
+
+            self.modals.push(Modal::ExportImage(ExportImage {
+                path: path.to_string_lossy().into_owned(),
+                scale: 1.0,
+                error: None,
+            }));
+        }
     }
 
     //-------------------------------------------------------------------------//
 
     pub fn close_graph(&mut self) {
-        self.active = Page::Blank;
+        self.close_tab(self.current);
     }
 
     //-------------------------------------------------------------------------//
-}
 
-//---------------------------------------------------------------------------//
+    /// Asks first if the project has unsaved changes
+    pub fn close_tab(&mut self, index: usize) {
+        match self.tabs.get(index) {
+            Some(project) if project.dirty => {
+                // one prompt at a time, keyed by id since other tabs may close
+                // while it is open
+                let id = project.id;
+                self.modals
+                    .retain(|modal| !matches!(modal, Modal::CloseTab(_)));
+                self.modals.push(Modal::CloseTab(id));
+            }
+            Some(_) => self.remove_tab(index),
+            None => {}
+        }
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// Closes the tab without saving
+    pub fn remove_tab(&mut self, index: usize) {
+        if index >= self.tabs.len() {
+            return;
+        }
+
+        self.tabs.remove(index);
+        if self.current > index || self.current >= self.tabs.len() {
+            self.current = self.current.saturating_sub(1);
+        }
+    }
 
-impl Default for App {
-    fn default() -> Self {
-        Self {
-            active: Page::Blank,
-            recent: vec![],
-            modals: vec![],
+    //-------------------------------------------------------------------------//
+
+    pub fn open_palette(&mut self) {
+        if !self
+            .modals
+            .iter()
+            .any(|modal| matches!(modal, Modal::Palette(_)))
+        {
+            self.modals.push(Modal::Palette(Default::default()));
         }
     }
+
+    //-------------------------------------------------------------------------//
 }
 
 ///////////////////////////////////////////////////////////////////////////////
 
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        if ctx.input_mut(|i| i.consume_shortcut(&PALETTE_SHORTCUT)) {
+            self.open_palette();
+        }
+        // text fields have their own undo, except for the graph editor
+        if !ctx.wants_keyboard_input() {
+            if let Some(project) = self.project_mut() {
+                undo_shortcuts(ctx, project);
+            }
+        }
+
         top_bar(self, ctx);
+        tab_bar(self, ctx);
         page_view(self, ctx);
         modal_view(self, ctx);
     }
@@ -93,3 +275,42 @@ impl eframe::App for App {
 }
 
 ///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn sessions_from_before_tabs_keep_their_project() {
+        let project = Project::new();
+        let session = format!(
+            "(active: Project({}), recent: [])",
+            ron::to_string(&project).unwrap()
+        );
+
+        let mut app: App = ron::from_str(&session).unwrap();
+        app.migrate();
+
+        assert_eq!(app.tabs.len(), 1);
+        assert_eq!(app.current, 0);
+        assert_eq!(app.tabs[0].text, project.text);
+
+        // and only once, the old key isn't written back
+        let saved = ron::to_string(&app).unwrap();
+        assert!(!saved.contains("active"));
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn blank_sessions_from_before_tabs_open_nothing() {
+        let mut app: App = ron::from_str("(active: Blank, recent: [])").unwrap();
+        app.migrate();
+
+        assert!(app.tabs.is_empty());
+    }
+
+    //-----------------------------------------------------------------------//
+}
@@ -1,8 +1,43 @@
 ///////////////////////////////////////////////////////////////////////////////
 
+use super::palette::Command;
+
+///////////////////////////////////////////////////////////////////////////////
+
 pub enum Modal {
-    FindFile,
-    SaveFile,
+    FindFile(PathInput),
+    SaveFile(PathInput),
+    ExportImage(ExportImage),
+    Palette(Palette),
+    /// Asks what to do with the unsaved changes of the project with this id
+    CloseTab(u64),
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[derive(Default)]
+pub struct PathInput {
+    pub path: String,
+    pub error: Option<String>,
+}
+
+//---------------------------------------------------------------------------//
+
+pub struct ExportImage {
+    pub path: String,
+    pub scale: f32,
+    pub error: Option<String>,
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Default)]
+pub struct Palette {
+    pub query: String,
+    /// Highlighted row in the ranked list
+    pub selected: usize,
+    /// Command waiting for its arguments
+    pub pending: Option<(Command, Vec<String>)>,
 }
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -1,33 +1,36 @@
 ///////////////////////////////////////////////////////////////////////////////
 
-use std::{f32::consts::PI, path::PathBuf};
+use std::{
+    collections::{BTreeSet, HashSet},
+    fs,
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
-use cs_240_library::{
-    algorithms::graphs::dfs::depth_first_search,
-    data_structures::graphs::{
-        weighted_graph::WeightedGraph, IDefiniteGraph, IGraph, IGraphEdgeWeightedMut, IGraphMut,
-        IWeightedGraph,
-    },
+use anyhow::{Error, Result};
+use cs_240_library::data_structures::graphs::{
+    weighted_graph::WeightedGraph, IDefiniteGraph, IGraph, IGraphEdgeWeightedMut, IGraphMut,
 };
-use egui::{
-    ahash::{HashMap, HashMapExt},
-    emath::TSTransform,
-    Pos2, Vec2,
+use egui::{emath::TSTransform, Pos2};
+
+use crate::{
+    document::{self, Document, GraphKind, Info, Layout},
+    graphs,
+    gui::{
+        history::{History, Snapshot},
+        search::Search,
+        worker::{Job, Task},
+    },
+    interactive,
+    layout::{self, smacof, Graphic},
+    render,
+    traversal::{self, Trace},
 };
 
 ///////////////////////////////////////////////////////////////////////////////
 
-fn typing(x: usize) -> f32 {
-    f32::from(i16::try_from(x).unwrap())
-}
-
-///////////////////////////////////////////////////////////////////////////////
-
-#[derive(serde::Deserialize, serde::Serialize)]
-pub enum Page {
-    Blank,
-    Project(Project),
-}
+/// Largest graph the stress readout is kept up to date for
+const MAX_STRESS_NODES: usize = 2000;
 
 ///////////////////////////////////////////////////////////////////////////////
 
@@ -37,7 +40,377 @@ pub struct Project {
     pub view: PanZoom,
     pub graph: WeightedGraph<String, i32>,
     pub text: String,
-    pub graphic: HashMap<String, Pos2>,
+
+    /// Kind of graph in the file, the text is in its format and saves keep it
+    #[serde(default)]
+    pub kind: GraphKind,
+
+    pub graphic: Graphic,
+
+    #[serde(default)]
+    pub info: Info,
+
+    /// Nodes that re-layouts leave alone
+    #[serde(default)]
+    pub pinned: BTreeSet<String>,
+
+    /// Re-layout on every edit instead of only placing new nodes
+    #[serde(default)]
+    pub auto_layout: bool,
+
+    #[serde(default)]
+    pub layout: layout::Params,
+
+    /// Changed since it was last saved
+    #[serde(default)]
+    pub dirty: bool,
+
+    /// Stress of the current layout, if the graph is small enough to measure
+    #[serde(skip)]
+    pub stress: Option<f32>,
+
+    #[serde(skip)]
+    pub selection: Selection,
+
+    #[serde(skip)]
+    pub player: Player,
+
+    #[serde(skip)]
+    pub route: Route,
+
+    #[serde(skip)]
+    pub console: Console,
+
+    /// What is wrong with the text, empty while it matches the graph
+    #[serde(skip)]
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// First lines of the folded blocks in the editor, from 0
+    #[serde(skip)]
+    pub folded: HashSet<usize>,
+
+    /// Node to center the canvas on next frame
+    #[serde(skip)]
+    pub focus: Option<String>,
+
+    #[serde(skip)]
+    pub search: Search,
+
+    /// View change to make once the canvas size is known
+    #[serde(skip)]
+    pub fit: Option<Fit>,
+
+    /// Overview of the whole layout in the corner of the canvas
+    #[serde(default)]
+    pub minimap: bool,
+
+    /// Stays the same while tabs open and close around it
+    #[serde(skip, default = "unique")]
+    pub id: u64,
+
+    /// Changes whenever the graph does, and is never shared between
+    /// projects, so views can cache what they work out from the graph
+    #[serde(skip, default = "unique")]
+    pub revision: u64,
+
+    /// Layout running in the background
+    #[serde(skip)]
+    pub job: Option<Job>,
+
+    /// Where the nodes are headed while they animate
+    #[serde(skip)]
+    target: Option<Graphic>,
+
+    #[serde(skip)]
+    history: History,
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Clone, Default, PartialEq)]
+pub enum Selection {
+    #[default]
+    None,
+    Node(String),
+    Edge(String, String),
+}
+
+//---------------------------------------------------------------------------//
+
+/// Step-by-step traversal shown on the canvas
+pub struct Player {
+    pub kind: traversal::Kind,
+    pub start: Option<String>,
+    pub trace: Option<Trace>,
+    /// Steps shown so far
+    pub step: usize,
+    pub playing: bool,
+    /// Steps per second while playing
+    pub speed: f32,
+    /// Time since the last step while playing
+    elapsed: f32,
+    pub error: Option<String>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Player {
+    //---------------------------------------------------------------------------//
+
+    /// Records a new trace from the start node and shows its first step
+    ///
+    /// Undirected graphs are traced as such, so each edge is classified once
+    pub fn run(&mut self, graph: &WeightedGraph<String, i32>, kind: GraphKind) -> Result<()> {
+        let start = self.start.as_ref().ok_or(Error::msg("No start node"))?;
+
+        let trace = match kind {
+            GraphKind::Undirected => {
+                Trace::new(&graphs::to_undirected(graph), self.kind, start, false)?
+            }
+            _ => Trace::new(graph, self.kind, start, true)?,
+        };
+        self.trace = Some(trace);
+        self.step = 1;
+        self.elapsed = 0.0;
+
+        Ok(())
+    }
+
+    //---------------------------------------------------------------------------//
+
+    pub fn steps(&self) -> usize {
+        self.trace.as_ref().map_or(0, |trace| trace.steps.len())
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Moves by `by` steps, staying inside the trace
+    pub fn seek(&mut self, by: isize) {
+        self.step = self
+            .step
+            .saturating_add_signed(by)
+            .clamp(1, self.steps().max(1));
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Advances while playing, returns whether it needs another frame
+    pub fn tick(&mut self, dt: f32) -> bool {
+        if !self.playing || self.trace.is_none() {
+            return false;
+        }
+
+        self.elapsed += dt;
+        while self.elapsed >= 1.0 / self.speed {
+            self.elapsed -= 1.0 / self.speed;
+            self.seek(1);
+        }
+
+        if self.step >= self.steps() {
+            self.playing = false;
+        }
+
+        self.playing
+    }
+
+    //---------------------------------------------------------------------------//
+}
+
+//---------------------------------------------------------------------------//
+
+impl Default for Player {
+    fn default() -> Self {
+        Self {
+            kind: Default::default(),
+            start: None,
+            trace: None,
+            step: 0,
+            playing: false,
+            speed: 2.0,
+            elapsed: 0.0,
+            error: None,
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Shortest path between two nodes, kept up to date with the graph
+pub struct Route {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Lowest total weight by Dijkstra, or fewest edges by BFS
+    pub weighted: bool,
+    /// Nothing until both ends are picked, then the path if there is one
+    pub result: Option<Result<Option<graphs::Path>, String>>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Route {
+    pub fn update(&mut self, graph: &WeightedGraph<String, i32>) {
+        self.result = match (&self.from, &self.to) {
+            (Some(from), Some(to)) if self.weighted => {
+                Some(graphs::lowest_cost(graph, from, to).map_err(|err| err.to_string()))
+            }
+            (Some(from), Some(to)) => Some(Ok(graphs::fewest_edges(graph, from, to))),
+            _ => None,
+        };
+    }
+
+    /// Nodes along the path, if one was found
+    pub fn path(&self) -> Option<&[String]> {
+        match &self.result {
+            Some(Ok(Some(path))) => Some(&path.nodes),
+            _ => None,
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+impl Default for Route {
+    fn default() -> Self {
+        Self {
+            from: None,
+            to: None,
+            weighted: true,
+            result: None,
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Command line under the canvas, with the commands from interactive mode
+#[derive(Default)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    /// Everything printed so far
+    pub log: String,
+    /// Commands run so far, oldest first
+    pub history: Vec<String>,
+    /// Where the arrow keys are in the history
+    pub history_pos: Option<usize>,
+}
+
+//---------------------------------------------------------------------------//
+
+/// Ways to frame the canvas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Fit {
+    /// Every node
+    All,
+    /// The selected node or edge
+    Selection,
+    /// Zoom to this scale around the middle of the canvas
+    Scale(f32),
+}
+
+//---------------------------------------------------------------------------//
+
+/// Something wrong with the graph text
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// Starting at 1, if known
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+//---------------------------------------------------------------------------//
+
+/// Line without indentation, list dashes or quotes
+fn unquote(line: &str) -> String {
+    line.trim()
+        .trim_start_matches("- ")
+        .replace(['"', '\''], "")
+}
+
+//---------------------------------------------------------------------------//
+
+/// Line where `node` is defined in the graph text, starting at 1, best effort
+pub fn node_line(text: &str, node: &str) -> Option<usize> {
+    let lines: Vec<String> = text.lines().map(unquote).collect();
+
+    // node names could clash with the keys above the adjacency lists
+    let start = lines.iter().position(|line| line == "adj:").unwrap_or(0);
+
+    let key = format!("{}:", node);
+    lines
+        .iter()
+        .enumerate()
+        .skip(start)
+        .find(|(_, line)| **line == key || line.starts_with(&format!("{} ", key)))
+        .map(|(i, _)| i + 1)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Line of `adj` in the adjacency list of `node`, best effort
+///
+/// Only looks at the list items in the node's own block, and they have to
+/// name `adj` exactly. A flow list on the node's line counts as well
+fn find_edge_line(text: &str, node: &str, adj: &str) -> Option<usize> {
+    let start = node_line(text, node)?;
+    let lines: Vec<&str> = text.lines().collect();
+
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let node_indent = indent(lines[start - 1]);
+
+    let (_, flow) = lines[start - 1].split_once(':')?;
+    let flow = flow.trim();
+    if let Some(items) = flow
+        .strip_prefix('[')
+        .and_then(|flow| flow.strip_suffix(']'))
+    {
+        return items
+            .split(',')
+            .any(|item| list_item(item) == adj)
+            .then_some(start);
+    }
+
+    let block: Vec<(usize, &str)> = lines
+        .iter()
+        .enumerate()
+        .skip(start)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .take_while(|(_, line)| {
+            indent(line) > node_indent
+                || (indent(line) == node_indent && line.trim_start().starts_with("- "))
+        })
+        .map(|(i, line)| (i, *line))
+        .collect();
+
+    // weights of weighted edges are list items too, but nested deeper
+    let item_indent = indent(block.first()?.1);
+    block
+        .into_iter()
+        .filter(|(_, line)| indent(line) == item_indent)
+        .find(|(_, line)| list_item(line) == adj)
+        .map(|(i, _)| i + 1)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Value of a list item, without its dashes or quotes
+///
+/// Weighted edges are `[node, weight]` pairs, so their node is behind two
+/// dashes
+fn list_item(line: &str) -> String {
+    let mut item = line.trim();
+    while let Some(rest) = item.strip_prefix("- ") {
+        item = rest.trim_start();
+    }
+    item.replace(['"', '\''], "")
+}
+
+//---------------------------------------------------------------------------//
+
+/// Number not handed out before, for project ids and revisions
+fn unique() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
 }
 
 //---------------------------------------------------------------------------//
@@ -64,238 +437,681 @@ impl Project {
             view: Default::default(),
             graph,
             text,
+            kind: GraphKind::Weighted,
             graphic: Default::default(),
+            info: Default::default(),
+            pinned: Default::default(),
+            auto_layout: false,
+            layout: Default::default(),
+            dirty: false,
+            stress: None,
+            selection: Default::default(),
+            player: Default::default(),
+            route: Default::default(),
+            console: Default::default(),
+            diagnostics: vec![],
+            folded: HashSet::new(),
+            focus: None,
+            search: Default::default(),
+            fit: None,
+            minimap: true,
+            id: unique(),
+            revision: unique(),
+            job: None,
+            target: None,
+            history: Default::default(),
         };
 
-        res.update_graphic();
+        res.graph_changed();
+        res.reset_history();
 
         res
     }
 
     //---------------------------------------------------------------------------//
 
+    pub fn open(path: PathBuf) -> Result<Self> {
+        let doc = document::load_weighted(&fs::read_to_string(&path)?)?;
+
+        let mut res = Self {
+            path: Some(path),
+            view: doc
+                .layout
+                .as_ref()
+                .and_then(|layout| layout.view.clone())
+                .unwrap_or_default(),
+            text: document::graph_text(&doc.graph, doc.kind)?,
+            kind: doc.kind,
+            graphic: doc.graphic(),
+            pinned: doc
+                .layout
+                .as_ref()
+                .map(|layout| layout.pinned.clone())
+                .unwrap_or_default(),
+            auto_layout: false,
+            layout: Default::default(),
+            dirty: false,
+            stress: None,
+            selection: Default::default(),
+            player: Default::default(),
+            route: Default::default(),
+            console: Default::default(),
+            diagnostics: vec![],
+            folded: HashSet::new(),
+            focus: None,
+            search: Default::default(),
+            fit: None,
+            minimap: true,
+            id: unique(),
+            revision: unique(),
+            job: None,
+            target: None,
+            history: Default::default(),
+            info: doc.info,
+            graph: doc.graph,
+        };
+
+        res.graph_changed();
+        res.reset_history();
+
+        Ok(res)
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Name for the tab, the file name if it has been saved
+    pub fn name(&self) -> String {
+        match self.path.as_ref().and_then(|path| path.file_name()) {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None if !self.info.title.is_empty() => self.info.title.clone(),
+            None => "Untitled".to_owned(),
+        }
+    }
+
+    //---------------------------------------------------------------------------//
+
+    pub fn save(&mut self) -> Result<()> {
+        let path = self.path.as_ref().ok_or(Error::msg("No file path set"))?;
+
+        let doc = Document {
+            kind: self.kind,
+            info: self.info.clone(),
+            layout: Some(Layout::new(
+                &self.graph,
+                &self.graphic,
+                &self.pinned,
+                Some(self.view.clone()),
+            )),
+            ..Document::new(self.graph.clone())
+        };
+
+        fs::write(path, document::save_weighted(&doc)?)?;
+        self.dirty = false;
+
+        Ok(())
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Parses the text, keeping the last valid graph and listing what is
+    /// wrong if it doesn't parse
     pub fn update_graph(&mut self) {
-        match serde_yaml::from_str(&self.text) {
-            Ok(val) => {
-                if self.check(&val) {
-                    self.graph = val;
-                    self.update_graphic();
+        self.dirty = true;
+        self.diagnostics = match self.parse() {
+            Ok(graph) => {
+                self.graph = graph;
+                self.graph_changed();
+                vec![]
+            }
+            Err(diagnostics) => diagnostics,
+        };
+        self.commit(true);
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Works out what is wrong with the text again, for projects restored
+    /// without their diagnostics
+    pub fn recheck(&mut self) {
+        self.diagnostics = self.parse().err().unwrap_or_default();
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Graph in the text, or what is wrong with it
+    fn parse(&self) -> std::result::Result<WeightedGraph<String, i32>, Vec<Diagnostic>> {
+        match document::parse_graph(&self.text, self.kind) {
+            Ok(graph) => {
+                let diagnostics = self.check(&graph);
+                if diagnostics.is_empty() {
+                    Ok(graph)
+                } else {
+                    Err(diagnostics)
                 }
             }
-            Err(_) => {}
+            Err(err) => Err(vec![Diagnostic {
+                line: err.location().map(|location| location.line()),
+                message: err.to_string(),
+            }]),
         }
     }
 
     //---------------------------------------------------------------------------//
 
-    pub fn check(&mut self, graph: &WeightedGraph<String, i32>) -> bool {
-        for node in graph.get_all() {
+    /// Edges to nodes that don't exist
+    pub fn check(&self, graph: &WeightedGraph<String, i32>) -> Vec<Diagnostic> {
+        let mut nodes = graph.get_all();
+        nodes.sort();
+
+        let mut res = vec![];
+        for node in nodes {
             for adj in graph.get_adj(&node) {
                 if !graph.contains(&adj) {
-                    return false;
+                    res.push(Diagnostic {
+                        line: find_edge_line(&self.text, &node, &adj),
+                        message: format!("{} -> {}: there is no node named {}", node, adj, adj),
+                    });
                 }
             }
         }
-        true
+        res
     }
 
     //---------------------------------------------------------------------------//
 
-    fn update_graphic(&mut self) {
-        self.graphic.clear();
-        let (roots, mut order, cyclic) = depth_first_search(self.graph.clone());
+    /// Whether the graph matches the text
+    pub fn in_sync(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    //---------------------------------------------------------------------------//
 
-        if cyclic {
-            println!("Cyclic graph");
-            let nodes = self.graph.get_all();
-            let len = typing(nodes.len());
+    /// Brings everything that depends on the graph up to date
+    fn graph_changed(&mut self) {
+        self.revision = unique();
+
+        // the trace was recorded on the old graph
+        self.player.trace = None;
+        self.player.playing = false;
+
+        self.route.update(&self.graph);
+        self.search.update(self.graph.get_all().iter());
+
+        let task = if self.auto_layout {
+            Task::Relayout
+        } else {
+            Task::Update
+        };
+        self.start_layout(task);
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Lays out the whole graph again, except for pinned nodes
+    pub fn relayout(&mut self) {
+        self.dirty = true;
+        self.start_layout(Task::Relayout);
+        self.commit(false);
+    }
+
+    //---------------------------------------------------------------------------//
+
+    pub fn unpin_all(&mut self) {
+        if !self.pinned.is_empty() {
+            self.pinned.clear();
+            self.dirty = true;
+            self.commit(false);
+        }
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Starts `task` in the background, cancelling the layout already running
+    fn start_layout(&mut self, task: Task) {
+        self.job = Some(Job::spawn(
+            task,
+            self.graph.clone(),
+            self.settled_graphic(),
+            self.pinned_graphic(),
+            self.layout.clone(),
+            self.graph.get_all().len() <= MAX_STRESS_NODES,
+        ));
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Picks up positions from the background layout and eases the nodes
+    /// toward them, returns whether anything is still going on
+    pub fn animate(&mut self, dt: f32) -> bool {
+        if let Some(job) = &self.job {
+            if let Some(done) = job.take_done() {
+                // the undo step the layout was started for ends up laid out
+                let mut graphic = done.graphic;
+                for node in &self.pinned {
+                    if let Some(pos) = self.graphic.get(node) {
+                        graphic.insert(node.clone(), *pos);
+                    }
+                }
+                self.history.amend_graphic(&graphic);
+                self.target = Some(graphic);
+                self.stress = done.stress;
+                self.job = None;
+            } else if let Some(positions) = job.take_positions() {
+                self.target = Some(positions);
+            } else if job.is_finished() {
+                // failed, keep what we have
+                self.job = None;
+                self.place_unplaced();
+            }
+        }
+
+        if let Some(target) = &self.target {
+            let ease = 1.0 - (-12.0 * dt).exp();
+            let mut moving = false;
+
+            self.graphic.retain(|node, _| target.contains_key(node));
+
+            for (node, to) in target {
+                match self.graphic.get_mut(node) {
+                    // pinned nodes may have been dragged since the job started
+                    Some(_) if self.pinned.contains(node) => {}
+                    Some(pos) if (*to - *pos).length() < 0.5 => *pos = *to,
+                    Some(pos) => {
+                        *pos += (*to - *pos) * ease;
+                        moving = true;
+                    }
+                    None => {
+                        self.graphic.insert(node.clone(), *to);
+                    }
+                }
+            }
 
-            for (i, node) in nodes.into_iter().enumerate() {
-                self.graphic.insert(
-                    node,
-                    Pos2 {
-                        x: (2.0 * PI * typing(i) / len).cos() * 50.0 * len + 300.0,
-                        y: (2.0 * PI * typing(i) / len).sin() * 50.0 * len + 300.0,
-                    },
-                );
+            if !moving && self.job.is_none() {
+                self.target = None;
             }
-            self.simulate();
-            // self.smacof();
+        }
+
+        self.job.is_some() || self.target.is_some()
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Stops the background layout, keeping the positions it got to
+    pub fn cancel_layout(&mut self) {
+        self.job = None;
+        self.place_unplaced();
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Gives nodes a layout never got to a spot next to their neighbours
+    fn place_unplaced(&mut self) {
+        let graphic = self.settled_graphic();
+        if self
+            .graph
+            .get_all()
+            .iter()
+            .all(|node| graphic.contains_key(node))
+        {
+            return;
+        }
+
+        // a full layout would hold up the UI, so a grid if nothing is placed
+        let graphic = if graphic.is_empty() {
+            layout::grid::layout(&self.graph, self.layout.spacing)
         } else {
-            println!("Acyclic graph");
+            layout::update(&self.graph, &graphic, &self.layout, &mut ())
+        };
+        self.history.amend_graphic(&graphic);
+        self.target = Some(graphic);
+    }
 
-            let mut x = 0.0;
-            let mut y = 0.0;
+    //---------------------------------------------------------------------------//
 
-            for root in roots {
-                y = 0.0;
-                x = self.update_graphic_rec(root, x + 100.0, y, 1);
+    /// Where the nodes end up once they stop animating
+    ///
+    /// Nodes that were just added or dragged are where they are now, since
+    /// the animation doesn't know about them
+    fn settled_graphic(&self) -> Graphic {
+        let mut graphic = self.target.clone().unwrap_or_default();
+        for (node, pos) in &self.graphic {
+            if self.pinned.contains(node) || !graphic.contains_key(node) {
+                graphic.insert(node.clone(), *pos);
             }
         }
+        graphic
     }
 
     //---------------------------------------------------------------------------//
 
-    fn update_graphic_rec(&mut self, root: String, mut x: f32, mut y: f32, weight: i32) -> f32 {
-        let mut adj = self.graph.get_adj_weighted(&root).into_iter();
+    fn pinned_graphic(&self) -> Graphic {
+        self.graphic
+            .iter()
+            .filter(|(node, _)| self.pinned.contains(*node))
+            .map(|(node, pos)| (node.clone(), *pos))
+            .collect()
+    }
 
-        let offset = f32::from(weight as u16) * 100.0;
-        println!("{}", offset);
-        y += offset;
+    //---------------------------------------------------------------------------//
 
-        self.graphic.insert(root.clone(), Pos2 { x, y });
+    /// Runs an interactive mode command against the graph, printing to the
+    /// console
+    pub fn run_command(&mut self, input: &str) {
+        let before = self.graph.clone();
+        let mut out = vec![];
+
+        // on a copy of the file's kind, so undirected edges stay symmetric
+        let res = match self.kind {
+            GraphKind::Weighted => interactive::run_command(input, &mut self.graph, &mut out),
+            GraphKind::Directed => {
+                let mut graph = graphs::to_directed(&self.graph);
+                let res = interactive::run_command(input, &mut graph, &mut out);
+                self.graph = graphs::to_weighted(&graph);
+                res
+            }
+            GraphKind::Undirected => {
+                let mut graph = graphs::to_undirected(&self.graph);
+                let res = interactive::run_command(input, &mut graph, &mut out);
+                self.graph = graphs::to_weighted(&graph);
+                res
+            }
+        };
 
-        if let Some((first, weight)) = adj.next() {
-            x = self.update_graphic_rec(first, x, y, weight);
+        let console = &mut self.console;
+        console.log.push_str(&format!("> {}\n", input.trim()));
+        console
+            .log
+            .push_str(String::from_utf8_lossy(&out).trim_start());
+        match res {
+            Ok(true) => {}
+            Ok(false) => console.open = false,
+            Err(err) => console.log.push_str(&format!("{}\n", err)),
         }
 
-        for (node, weight) in adj {
-            // self.graphic.insert(node.clone(), Pos2 { x, y });
-            x = self.update_graphic_rec(node, x + 100.0, y, weight);
+        let after = serde_yaml::to_string(&self.graph).unwrap_or_default();
+        if after == serde_yaml::to_string(&before).unwrap_or_default() {
+            return;
+        }
+
+        if !self.in_sync() {
+            // rewriting the text would throw away what is being typed
+            self.graph = before;
+            self.console
+                .log
+                .push_str("Fix the errors in the text first, nothing changed\n");
+        } else if self.check(&self.graph).is_empty() {
+            self.prune_info();
+            self.sync_text();
+        } else {
+            self.graph = before;
+            self.console
+                .log
+                .push_str("Edges have to be between existing nodes, nothing changed\n");
         }
+    }
+
+    //---------------------------------------------------------------------------//
 
-        x
+    /// Rewrites the text from the graph after an edit on the canvas
+    fn sync_text(&mut self) {
+        self.text = document::graph_text(&self.graph, self.kind).unwrap_or_default();
+        self.diagnostics.clear();
+        self.dirty = true;
+        self.graph_changed();
+        self.commit(false);
     }
 
     //---------------------------------------------------------------------------//
 
-    pub fn smacof(&mut self) {}
+    /// Adds a node with a fresh name at `pos` and returns the name
+    pub fn add_node(&mut self, pos: Pos2) -> String {
+        let name = (1..)
+            .map(|i| format!("node {}", i))
+            .find(|name| !self.graph.contains(name))
+            .unwrap();
+
+        self.graph.insert_node(name.clone());
+        self.graphic.insert(name.clone(), pos);
+        self.sync_text();
+
+        name
+    }
 
     //---------------------------------------------------------------------------//
 
-    pub fn stress(&mut self) {}
+    pub fn remove_node(&mut self, node: &str) {
+        graphs::remove_node(&mut self.graph, node);
+        self.graphic.remove(node);
+        self.pinned.remove(node);
+        self.prune_info();
+        for end in [&mut self.route.from, &mut self.route.to] {
+            if end.as_deref() == Some(node) {
+                *end = None;
+            }
+        }
+        self.sync_text();
+    }
 
     //---------------------------------------------------------------------------//
 
-    pub fn simulate(&mut self) {
-        let mut velocities = HashMap::new();
-        let mut accelerations = HashMap::new();
+    /// Fails if `new` is empty or already taken
+    pub fn rename_node(&mut self, old: &str, new: &str) -> Result<()> {
+        let new = new.trim();
 
-        for node in self.graph.get_all() {
-            velocities.insert(node.clone(), Vec2 { x: 0.0, y: 0.0 });
-            accelerations.insert(node, Vec2 { x: 0.0, y: 0.0 });
+        if new == old {
+            return Ok(());
+        }
+        if new.is_empty() || self.graph.contains(&new.to_owned()) {
+            return Err(Error::msg(format!("Can't rename {} to \"{}\"", old, new)));
         }
 
-        let steps = 300;
-        for step in 1..steps + 1 {
-            // println!("--- {}/{}", step, steps);
+        graphs::rename_node(&mut self.graph, old, new);
+
+        if let Some(pos) = self.graphic.remove(old) {
+            self.graphic.insert(new.to_owned(), pos);
+        }
+        if self.pinned.remove(old) {
+            self.pinned.insert(new.to_owned());
+        }
+        if let Some(attributes) = self.info.nodes.remove(old) {
+            self.info.nodes.insert(new.to_owned(), attributes);
+        }
+        for edge in &mut self.info.edges {
+            if edge.from == old {
+                edge.from = new.to_owned();
+            }
+            if edge.to == old {
+                edge.to = new.to_owned();
+            }
+        }
+        if self.selection == Selection::Node(old.to_owned()) {
+            self.selection = Selection::Node(new.to_owned());
+        }
+        for end in [&mut self.route.from, &mut self.route.to] {
+            if end.as_deref() == Some(old) {
+                *end = Some(new.to_owned());
+            }
+        }
 
-            for node in self.graph.get_all() {
-                let node_pos = self.graphic.get(&node).unwrap();
-                // println!("> {} @ {:?}", node, node_pos);
+        self.sync_text();
 
-                let adj_nodes = self.graph.get_adj_weighted(&node);
+        Ok(())
+    }
 
-                for other in self.graph.get_all() {
-                    if other != node {
-                        let adj_pos = self.graphic.get(&other).unwrap();
+    //---------------------------------------------------------------------------//
 
-                        let diff = adj_pos.to_vec2() - node_pos.to_vec2();
-                        let dist = (diff).length();
+    /// Inserts the edge, or changes its weight if it already exists
+    pub fn set_edge(&mut self, from: &str, to: &str, weight: i32) {
+        graphs::set_edge(&mut self.graph, from, to, weight);
+        if self.kind == GraphKind::Undirected {
+            graphs::set_edge(&mut self.graph, to, from, weight);
+        }
+        self.sync_text();
+    }
 
-                        let spring_char;
-                        let relaxed_dist;
-                        let node_mass;
+    //---------------------------------------------------------------------------//
 
-                        let mut num_connections = 0;
-                        let mut total_weight = 0;
+    pub fn remove_edge(&mut self, from: &str, to: &str) {
+        graphs::remove_edge(&mut self.graph, from, to);
+        if self.kind == GraphKind::Undirected {
+            graphs::remove_edge(&mut self.graph, to, from);
+        }
+        self.prune_info();
+        self.sync_text();
+    }
 
-                        for (_, weight) in adj_nodes.iter().filter(|(x, _)| *x == other) {
-                            num_connections += 1;
-                            total_weight += weight;
-                        }
+    //---------------------------------------------------------------------------//
 
-                        for (_, weight) in self
-                            .graph
-                            .get_adj_weighted(&other)
-                            .iter()
-                            .filter(|(x, _)| *x == node)
-                        {
-                            num_connections += 1;
-                            total_weight += weight;
-                        }
+    /// Drops the attributes of nodes and edges that are no longer in the graph
+    fn prune_info(&mut self) {
+        let graph = &self.graph;
+        self.info.nodes.retain(|node, _| graph.contains(node));
+        self.info.edges.retain(|edge| {
+            graph.contains(&edge.from) && graph.get_adj(&edge.from).contains(&edge.to)
+        });
+    }
 
-                        if num_connections > 0 {
-                            let mean_weight = total_weight / num_connections;
+    //---------------------------------------------------------------------------//
 
-                            spring_char = 0.7;
-                            relaxed_dist = f32::from(mean_weight as u16) * 100.0;
-                            node_mass = 100.0;
-                        } else {
-                            spring_char = 0.5;
-                            relaxed_dist = 300.0;
-                            node_mass = 500.0;
-                        }
+    pub fn remove_selection(&mut self) {
+        match std::mem::take(&mut self.selection) {
+            Selection::None => {}
+            Selection::Node(node) => self.remove_node(&node),
+            Selection::Edge(from, to) => self.remove_edge(&from, &to),
+        }
+    }
 
-                        // if adj_nodes.iter().any(|(x, _)| *x == other)
-                        //     || self.graph.get_adj(&other).contains(&node)
-                        // {
-                        //     spring_char = 0.7;
-                        //     relaxed_dist = 100.0;
-                        //     node_mass = 100.0;
-                        // } else {
-                        //     spring_char = 0.5;
-                        //     relaxed_dist = 150.0;
-                        //     node_mass = 500.0;
-                        // }
+    //---------------------------------------------------------------------------//
 
-                        let restoring_force = -spring_char * (dist - relaxed_dist);
-                        let acc = restoring_force / node_mass;
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            text: self.text.clone(),
+            graph: self.graph.clone(),
+            graphic: self.settled_graphic(),
+            pinned: self.pinned.clone(),
+            info: self.info.clone(),
+            diagnostics: self.diagnostics.clone(),
+        }
+    }
 
-                        // println!(
-                        //     "> {} @ {:?} <<< {:?} | {:?} | {:?} >>> {} @ {:?}",
-                        //     node, node_pos, dist, restoring_force, acc, other, adj_pos
-                        // );
+    //---------------------------------------------------------------------------//
 
-                        let acc_vec = acc * diff.normalized() + diff.normalized();
+    /// Starts the undo history from the project as it is now
+    pub fn reset_history(&mut self) {
+        let state = self.snapshot();
+        self.history.reset(state);
+    }
 
-                        let node_acc = accelerations.get_mut(&node).unwrap();
-                        *node_acc = *node_acc - acc_vec;
+    //---------------------------------------------------------------------------//
 
-                        let other_acc = accelerations.get_mut(&other).unwrap();
-                        *other_acc = *other_acc + acc_vec;
-                    }
-                }
+    /// Records an undo step after an edit, merged with the last one while
+    /// `typing`
+    pub fn commit(&mut self, typing: bool) {
+        let state = self.snapshot();
+        self.history.push(state, typing);
+    }
 
-                // for adj in self.graph.get_adj(&node) {
-                //     let adj_pos = self.graphic.get(&adj).unwrap();
+    //---------------------------------------------------------------------------//
 
-                //     let diff = adj_pos.to_vec2() - node_pos.to_vec2();
-                //     let dist = (diff).length();
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
 
-                //     let spring_char = 0.7;
-                //     let relaxed_dist = 100.0;
-                //     let node_mass = 50.0;
+    //---------------------------------------------------------------------------//
 
-                //     let restoring_force = -spring_char * (dist - relaxed_dist);
-                //     let acc = restoring_force / node_mass;
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
 
-                //     let acc_vec = acc * diff.normalized() + diff.normalized();
+    //---------------------------------------------------------------------------//
 
-                //     let node_acc = accelerations.get_mut(&node).unwrap();
-                //     *node_acc = *node_acc - acc_vec;
+    pub fn undo(&mut self) {
+        if let Some(state) = self.history.undo() {
+            self.restore(state);
+        }
+    }
 
-                //     let adj_acc = accelerations.get_mut(&adj).unwrap();
-                //     *adj_acc = *adj_acc + acc_vec;
-                // }
-            }
+    //---------------------------------------------------------------------------//
 
-            for node in self.graph.get_all() {
-                let node_pos = self.graphic.get_mut(&node).unwrap();
-                let node_vel = velocities.get_mut(&node).unwrap();
-                let node_acc = accelerations.get_mut(&node).unwrap();
+    pub fn redo(&mut self) {
+        if let Some(state) = self.history.redo() {
+            self.restore(state);
+        }
+    }
 
-                *node_vel += *node_acc;
-                *node_vel -= node_vel.normalized();
-                *node_pos += *node_vel;
+    //---------------------------------------------------------------------------//
 
-                // println!(
-                //     "> {} @ {:?} v {:?} a {:?}",
-                //     node, node_pos, node_vel, node_acc
-                // );
-                *node_acc = Vec2 { x: 0.0, y: 0.0 };
+    /// Puts back an undo step, animating the nodes to where they were
+    fn restore(&mut self, state: Snapshot) {
+        self.job = None;
+
+        self.text = state.text;
+        self.graph = state.graph;
+        self.revision = unique();
+        self.pinned = state.pinned;
+        self.info = state.info;
+        self.diagnostics = state.diagnostics;
+        self.dirty = true;
+
+        // pinned nodes don't animate
+        for node in &self.pinned {
+            if let Some(pos) = state.graphic.get(node) {
+                self.graphic.insert(node.clone(), *pos);
             }
         }
+        self.target = Some(state.graphic);
+
+        let selection_gone = match &self.selection {
+            Selection::None => false,
+            Selection::Node(node) => !self.graph.contains(node),
+            Selection::Edge(from, to) => !self.graph.get_adj(from).contains(to),
+        };
+        if selection_gone {
+            self.selection = Selection::None;
+        }
+
+        self.player.trace = None;
+        self.player.playing = false;
+        self.route.update(&self.graph);
+        self.search.update(self.graph.get_all().iter());
+
+        // the step was taken before its layout finished
+        let target = self.target.as_ref().unwrap_or(&self.graphic);
+        if self
+            .graph
+            .get_all()
+            .iter()
+            .any(|node| !target.contains_key(node))
+        {
+            self.start_layout(Task::Update);
+        }
+    }
+
+    //---------------------------------------------------------------------------//
+
+    pub fn export_image(&self, path: &str, scale: f32) -> Result<()> {
+        let svg = render::svg(&self.graph, self.kind, &self.graphic)?;
+        fs::write(path, render::png(&svg, scale)?)?;
+        Ok(())
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Runs stress majorization from the current positions, except for
+    /// pinned nodes
+    pub fn smacof(&mut self) {
+        self.dirty = true;
+        self.start_layout(Task::Refine);
+        self.commit(false);
+    }
+
+    //---------------------------------------------------------------------------//
+
+    /// Measures the stress of the current positions, skipped for large graphs
+    /// since it needs all shortest paths
+    pub fn update_stress(&mut self) {
+        self.stress = (self.graphic.len() <= MAX_STRESS_NODES)
+            .then(|| smacof::stress(&self.graph, &self.graphic, self.layout.smacof.edge_length));
     }
 
     //---------------------------------------------------------------------------//
@@ -310,7 +1126,29 @@ impl Default for Project {
             view: Default::default(),
             graph: WeightedGraph::new(),
             text: Default::default(),
+            kind: Default::default(),
             graphic: Default::default(),
+            info: Default::default(),
+            pinned: Default::default(),
+            auto_layout: false,
+            layout: Default::default(),
+            dirty: false,
+            stress: None,
+            selection: Default::default(),
+            player: Default::default(),
+            route: Default::default(),
+            console: Default::default(),
+            diagnostics: vec![],
+            folded: HashSet::new(),
+            focus: None,
+            search: Default::default(),
+            fit: None,
+            minimap: true,
+            id: unique(),
+            revision: unique(),
+            job: None,
+            target: None,
+            history: Default::default(),
         }
     }
 }
@@ -321,6 +1159,98 @@ impl Default for Project {
 pub struct PanZoom {
     pub transform: TSTransform,
     pub drag_value: f32,
+
+    /// Where the view is headed while it animates
+    #[serde(skip)]
+    pub target: Option<TSTransform>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl PanZoom {
+    //-------------------------------------------------------------------------//
+
+    /// Moves the view to `target` over the next few frames
+    pub fn animate_to(&mut self, target: TSTransform) {
+        self.target = Some(target);
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// Eases toward the target, returns whether it is still moving
+    pub fn animate(&mut self, dt: f32) -> bool {
+        let Some(target) = self.target else {
+            return false;
+        };
+
+        let ease = 1.0 - (-12.0 * dt).exp();
+        let view = &mut self.transform;
+
+        if (target.translation - view.translation).length() < 0.5
+            && (target.scaling - view.scaling).abs() < 1e-3
+        {
+            *view = target;
+            self.target = None;
+            return false;
+        }
+
+        view.translation += (target.translation - view.translation) * ease;
+        view.scaling += (target.scaling - view.scaling) * ease;
+        true
+    }
+
+    //-------------------------------------------------------------------------//
 }
 
 ///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //-----------------------------------------------------------------------//
+
+    const WEIGHTED: &str = "\
+adj:
+  a:
+  - - bc
+    - 2
+  - - b
+    - 1
+  c:
+  - - x
+    - 1
+";
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn edge_lines_match_whole_names() {
+        assert_eq!(find_edge_line(WEIGHTED, "a", "b"), Some(5));
+        assert_eq!(find_edge_line(WEIGHTED, "a", "bc"), Some(3));
+
+        let directed = "adj:\n  a:\n  - abc\n  - \"b\"\n  b: []\n";
+        assert_eq!(find_edge_line(directed, "a", "b"), Some(4));
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn edge_lines_stay_in_the_node_block() {
+        // x is only in c's list, and 2 is a weight
+        assert_eq!(find_edge_line(WEIGHTED, "a", "x"), None);
+        assert_eq!(find_edge_line(WEIGHTED, "a", "2"), None);
+        assert_eq!(find_edge_line(WEIGHTED, "c", "x"), Some(8));
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn edge_lines_in_flow_lists() {
+        let text = "adj:\n  a: [bc, 'b']\n  b: []\n";
+        assert_eq!(find_edge_line(text, "a", "b"), Some(2));
+        assert_eq!(find_edge_line(text, "a", "c"), None);
+    }
+
+    //-----------------------------------------------------------------------//
+}
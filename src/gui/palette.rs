@@ -0,0 +1,339 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use cs_240_library::data_structures::graphs::weighted_graph::WeightedGraph;
+use egui::{emath::TSTransform, Key, KeyboardShortcut, Modifiers};
+
+use crate::{
+    gui::{
+        modals::{Modal, PathInput},
+        pages::{Fit, Project, Selection},
+        App,
+    },
+    interactive::{self, levenshtein_distance, ActionInfo},
+    layout, traversal,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+
+pub const PALETTE_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::P);
+
+pub const SEARCH_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::F);
+
+pub const UNDO_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+
+pub const REDO_SHORTCUT: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z);
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Everything the command palette can do
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    NewGraph,
+    OpenGraph,
+    SaveGraph,
+    SaveGraphAs,
+    ExportImage,
+    CloseGraph,
+    Quit,
+    Undo,
+    Redo,
+    Relayout,
+    /// Switch to the method and re-layout
+    Layout(layout::Method),
+    Refine,
+    MeasureStress,
+    UnpinAll,
+    ResetView,
+    FitAll,
+    FitSelection,
+    ToggleMinimap,
+    ToggleConsole,
+    /// Trace from the selected node in the traversal player
+    Trace(traversal::Kind),
+    ClearRoute,
+    /// Interactive mode command, run in the console
+    Repl(ActionInfo),
+}
+
+//---------------------------------------------------------------------------//
+
+impl Command {
+    //-------------------------------------------------------------------------//
+
+    pub fn all() -> Vec<Command> {
+        let mut res = vec![
+            Command::NewGraph,
+            Command::OpenGraph,
+            Command::SaveGraph,
+            Command::SaveGraphAs,
+            Command::ExportImage,
+            Command::CloseGraph,
+            Command::Quit,
+            Command::Undo,
+            Command::Redo,
+            Command::Relayout,
+        ];
+
+        res.extend(layout::Method::ALL.map(Command::Layout));
+        res.extend([
+            Command::Refine,
+            Command::MeasureStress,
+            Command::UnpinAll,
+            Command::ResetView,
+            Command::FitAll,
+            Command::FitSelection,
+            Command::ToggleMinimap,
+            Command::ToggleConsole,
+            Command::Trace(traversal::Kind::Bfs),
+            Command::Trace(traversal::Kind::Dfs),
+            Command::ClearRoute,
+        ]);
+        res.extend(
+            interactive::action_infos::<WeightedGraph<String, i32>>()
+                .into_iter()
+                .filter(|action| action.name != "quit")
+                .map(Command::Repl),
+        );
+
+        res
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn label(&self) -> String {
+        match self {
+            Command::NewGraph => "File: New graph".to_owned(),
+            Command::OpenGraph => "File: Open graph ...".to_owned(),
+            Command::SaveGraph => "File: Save".to_owned(),
+            Command::SaveGraphAs => "File: Save as ...".to_owned(),
+            Command::ExportImage => "File: Export image ...".to_owned(),
+            Command::CloseGraph => "File: Close graph".to_owned(),
+            Command::Quit => "File: Quit".to_owned(),
+            Command::Undo => "Edit: Undo".to_owned(),
+            Command::Redo => "Edit: Redo".to_owned(),
+            Command::Relayout => "Layout: Re-layout".to_owned(),
+            Command::Layout(method) => format!("Layout: {}", method.name()),
+            Command::Refine => "Layout: Refine with stress majorization".to_owned(),
+            Command::MeasureStress => "Analysis: Measure stress".to_owned(),
+            Command::UnpinAll => "View: Unpin all".to_owned(),
+            Command::ResetView => "View: Reset view".to_owned(),
+            Command::FitAll => "View: Fit all".to_owned(),
+            Command::FitSelection => "View: Fit selection".to_owned(),
+            Command::ToggleMinimap => "View: Toggle minimap".to_owned(),
+            Command::ToggleConsole => "View: Toggle console".to_owned(),
+            Command::Trace(kind) => format!("Analysis: {} search from selected", kind.name()),
+            Command::ClearRoute => "Analysis: Clear route".to_owned(),
+            Command::Repl(action) => format!("Console: {} {}", action.name, action.pattern),
+        }
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            Command::Repl(action) => Some(&action.desc),
+            _ => None,
+        }
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn needs_project(&self) -> bool {
+        !matches!(self, Command::NewGraph | Command::OpenGraph | Command::Quit)
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// Arguments to ask for before running, from the action's pattern
+    pub fn arguments(&self) -> Vec<String> {
+        match self {
+            Command::Repl(action) => action
+                .pattern
+                .split_whitespace()
+                .map(str::to_owned)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    //-------------------------------------------------------------------------//
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// How badly `label` matches `query`, lower is better
+///
+/// Every word of the query is compared against the start of each word of the
+/// label, so `exp img` finds "Export image"
+pub fn rank(query: &str, label: &str) -> usize {
+    let label = label.to_lowercase();
+    let words: Vec<&str> = label
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    query
+        .to_lowercase()
+        .split_whitespace()
+        .map(|part| {
+            let len = part.chars().count();
+            words
+                .iter()
+                .map(|word| {
+                    let start: String = word.chars().take(len).collect();
+                    levenshtein_distance(part, &start)
+                })
+                .min()
+                .unwrap_or(len)
+        })
+        .sum()
+}
+
+//---------------------------------------------------------------------------//
+
+/// Whether a required argument was left empty
+pub fn missing_argument(patterns: &[String], args: &[String]) -> bool {
+    patterns
+        .iter()
+        .zip(args)
+        .any(|(pattern, arg)| pattern.starts_with('<') && arg.trim().is_empty())
+}
+
+//---------------------------------------------------------------------------//
+
+/// Undoes or redoes if the shortcut was pressed
+pub fn undo_shortcuts(ctx: &egui::Context, project: &mut Project) {
+    // checked first, since the undo shortcut matches with shift held too
+    if ctx.input_mut(|i| i.consume_shortcut(&REDO_SHORTCUT)) {
+        project.redo();
+    } else if ctx.input_mut(|i| i.consume_shortcut(&UNDO_SHORTCUT)) {
+        project.undo();
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+impl App {
+    //-------------------------------------------------------------------------//
+
+    pub fn run_command(&mut self, ctx: &egui::Context, command: &Command, args: &[String]) {
+        match command {
+            Command::NewGraph => self.new_graph(),
+            Command::OpenGraph => self.find_graph(),
+            Command::SaveGraph => {
+                if let Err(err) = self.save_graph() {
+                    self.modals.push(Modal::SaveFile(PathInput {
+                        path: Default::default(),
+                        error: Some(err.to_string()),
+                    }));
+                }
+            }
+            Command::SaveGraphAs => self.save_graph_as(),
+            Command::ExportImage => self.export_image(),
+            Command::CloseGraph => self.close_graph(),
+            Command::Quit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            _ => {
+                if let Some(project) = self.project_mut() {
+                    match command {
+                        Command::Undo => project.undo(),
+                        Command::Redo => project.redo(),
+                        Command::Relayout => project.relayout(),
+                        Command::Layout(method) => {
+                            project.layout.method = *method;
+                            project.relayout();
+                        }
+                        Command::Refine => project.smacof(),
+                        Command::MeasureStress => project.update_stress(),
+                        Command::UnpinAll => project.unpin_all(),
+                        Command::ResetView => project.view.animate_to(TSTransform::default()),
+                        Command::FitAll => project.fit = Some(Fit::All),
+                        Command::FitSelection => project.fit = Some(Fit::Selection),
+                        Command::ToggleMinimap => project.minimap = !project.minimap,
+                        Command::ToggleConsole => project.console.open = !project.console.open,
+                        Command::Trace(kind) => {
+                            let player = &mut project.player;
+                            player.kind = *kind;
+                            if let Selection::Node(node) = &project.selection {
+                                player.start = Some(node.clone());
+                            }
+                            player.error = player
+                                .run(&project.graph, project.kind)
+                                .err()
+                                .map(|err| err.to_string());
+                        }
+                        Command::ClearRoute => {
+                            project.route.from = None;
+                            project.route.to = None;
+                            project.route.update(&project.graph);
+                        }
+                        Command::Repl(action) => {
+                            // quoted, so names with spaces stay one argument
+                            let input = std::iter::once(action.name.clone())
+                                .chain(
+                                    args.iter()
+                                        .map(|arg| arg.trim())
+                                        .filter(|arg| !arg.is_empty())
+                                        .map(interactive::quote),
+                                )
+                                .collect::<Vec<_>>()
+                                .join(" ");
+
+                            project.console.open = true;
+                            project.console.history.push(input.clone());
+                            project.run_command(&input);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    //-------------------------------------------------------------------------//
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn rank_matches_word_starts() {
+        assert_eq!(rank("exp ima", "Export image"), 0);
+        assert_eq!(rank("exp img", "Export image"), 1);
+        assert_eq!(rank("EXPORT", "Export image"), 0);
+        assert_eq!(rank("", "Export image"), 0);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn rank_prefers_closer_labels() {
+        assert!(rank("sa as", "Save graph as") < rank("sa as", "Save graph"));
+        assert!(rank("expot", "Export image") < rank("expot", "Open graph"));
+        assert!(rank("layout grid", "Layout: Grid") < rank("layout grid", "Layout: Radial"));
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn missing_argument_only_counts_required_ones() {
+        let patterns = ["<from>", "<to>", "[weight]"].map(String::from);
+
+        assert!(missing_argument(
+            &patterns,
+            &["a", " ", ""].map(String::from)
+        ));
+        assert!(!missing_argument(
+            &patterns,
+            &["a", "b", ""].map(String::from)
+        ));
+    }
+
+    //-----------------------------------------------------------------------//
+}
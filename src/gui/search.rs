@@ -0,0 +1,281 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use std::collections::HashSet;
+
+use regex::RegexBuilder;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// How the query is matched against node names, ignoring case
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    Prefix,
+    #[default]
+    Substring,
+    Regex,
+    /// Letters of the query in order, with anything between them
+    Fuzzy,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Mode {
+    pub const ALL: [Mode; 4] = [Mode::Prefix, Mode::Substring, Mode::Regex, Mode::Fuzzy];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Prefix => "Prefix",
+            Mode::Substring => "Substring",
+            Mode::Regex => "Regex",
+            Mode::Fuzzy => "Fuzzy",
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Finds nodes by name on the canvas
+#[derive(Default)]
+pub struct Search {
+    pub query: String,
+    pub mode: Mode,
+    /// Fade out the nodes that don't match
+    pub dim: bool,
+    /// Matching nodes, best first
+    pub hits: Vec<String>,
+    /// Hit the view was last moved to
+    pub current: Option<usize>,
+    /// Why the regex doesn't compile
+    pub error: Option<String>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Search {
+    //-------------------------------------------------------------------------//
+
+    /// Whether there is a query to highlight
+    pub fn active(&self) -> bool {
+        !self.query.is_empty() && self.error.is_none()
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// Matches the query against `nodes` again
+    pub fn update<'a>(&mut self, nodes: impl Iterator<Item = &'a String>) {
+        let current = self.current.and_then(|i| self.hits.get(i)).cloned();
+
+        self.error = None;
+        self.hits = if self.query.is_empty() {
+            vec![]
+        } else {
+            match self.ranked(nodes) {
+                Ok(hits) => hits,
+                Err(err) => {
+                    self.error = Some(err);
+                    vec![]
+                }
+            }
+        };
+
+        // stay on the same node if it still matches
+        self.current = current.and_then(|node| self.hits.iter().position(|hit| *hit == node));
+    }
+
+    //-------------------------------------------------------------------------//
+
+    fn ranked<'a>(&self, nodes: impl Iterator<Item = &'a String>) -> Result<Vec<String>, String> {
+        let query = self.query.to_lowercase();
+
+        let mut hits: Vec<(usize, String)> = match self.mode {
+            Mode::Prefix => nodes
+                .filter(|node| node.to_lowercase().starts_with(&query))
+                .map(|node| (0, node.clone()))
+                .collect(),
+            Mode::Substring => nodes
+                .filter_map(|node| Some((node.to_lowercase().find(&query)?, node.clone())))
+                .collect(),
+            Mode::Regex => {
+                let regex = RegexBuilder::new(&self.query)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|err| err.to_string())?;
+                nodes
+                    .filter(|node| regex.is_match(node))
+                    .map(|node| (0, node.clone()))
+                    .collect()
+            }
+            Mode::Fuzzy => nodes
+                .filter_map(|node| {
+                    Some((fuzzy_spread(&query, &node.to_lowercase())?, node.clone()))
+                })
+                .collect(),
+        };
+
+        hits.sort();
+        Ok(hits.into_iter().map(|(_, node)| node).collect())
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// Moves to the next hit, or the previous one going `back`
+    pub fn step(&mut self, back: bool) -> Option<String> {
+        let len = self.hits.len();
+        if len == 0 {
+            return None;
+        }
+
+        let next = match (self.current, back) {
+            (None, false) => 0,
+            (None, true) => len - 1,
+            (Some(i), false) => (i + 1) % len,
+            (Some(i), true) => (i + len - 1) % len,
+        };
+        self.current = Some(next);
+        self.hits.get(next).cloned()
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn current_hit(&self) -> Option<&String> {
+        self.current.and_then(|i| self.hits.get(i))
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn hit_set(&self) -> HashSet<&String> {
+        self.hits.iter().collect()
+    }
+
+    //-------------------------------------------------------------------------//
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// How many letters apart the first and last letter of the query are in
+/// `name`, taking the earliest match, or None if they aren't all there
+fn fuzzy_spread(query: &str, name: &str) -> Option<usize> {
+    let mut chars = name.char_indices();
+    let mut first = None;
+    let mut last = 0;
+
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let (i, _) = chars.find(|(_, c)| *c == wanted)?;
+        first.get_or_insert(i);
+        last = i;
+    }
+
+    Some(last - first.unwrap_or(0))
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //-----------------------------------------------------------------------//
+
+    const NODES: [&str; 5] = ["Alpha", "alps", "Palace", "Lap", "beta"];
+
+    fn search(query: &str, mode: Mode) -> Search {
+        let mut search = Search {
+            query: query.to_owned(),
+            mode,
+            ..Search::default()
+        };
+        search.update(nodes().iter());
+        search
+    }
+
+    fn nodes() -> Vec<String> {
+        NODES.iter().map(|node| node.to_string()).collect()
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn prefix_ignores_case() {
+        assert_eq!(search("AL", Mode::Prefix).hits, ["Alpha", "alps"]);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn substring_ranks_earlier_matches_first() {
+        assert_eq!(search("la", Mode::Substring).hits, ["Lap", "Palace"]);
+        assert_eq!(
+            search("a", Mode::Substring).hits,
+            ["Alpha", "alps", "Lap", "Palace", "beta"]
+        );
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn regex_ignores_case() {
+        assert_eq!(search("^a.*s$", Mode::Regex).hits, ["alps"]);
+        assert_eq!(search("^[AB]", Mode::Regex).hits, ["Alpha", "alps", "beta"]);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn invalid_regex_is_an_error_without_hits() {
+        let search = search("(al", Mode::Regex);
+        assert!(search.error.is_some());
+        assert!(search.hits.is_empty());
+        assert!(!search.active());
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn fuzzy_ranks_tighter_matches_first() {
+        assert_eq!(search("ap", Mode::Fuzzy).hits, ["Lap", "Alpha", "alps"]);
+        assert_eq!(fuzzy_spread("a p", "lap"), Some(1));
+        assert_eq!(fuzzy_spread("pa", "lap"), None);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn empty_query_has_no_hits() {
+        let search = search("", Mode::Substring);
+        assert!(search.hits.is_empty());
+        assert!(!search.active());
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn step_wraps_around() {
+        let mut search = search("al", Mode::Prefix);
+        assert_eq!(search.step(true).as_deref(), Some("alps"));
+        assert_eq!(search.step(false).as_deref(), Some("Alpha"));
+        assert_eq!(search.step(true).as_deref(), Some("alps"));
+        assert_eq!(search.step(false).as_deref(), Some("Alpha"));
+
+        assert_eq!(Search::default().step(false), None);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn update_stays_on_the_current_node() {
+        let mut search = search("al", Mode::Substring);
+        search.step(false);
+        search.step(false);
+        assert_eq!(search.current_hit().map(String::as_str), Some("alps"));
+
+        search.query = "l".to_owned();
+        search.update(nodes().iter());
+        assert_eq!(search.current_hit().map(String::as_str), Some("alps"));
+
+        search.query = "b".to_owned();
+        search.update(nodes().iter());
+        assert_eq!(search.current, None);
+    }
+
+    //-----------------------------------------------------------------------//
+}
@@ -0,0 +1,86 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use egui::{Key, RichText};
+
+use crate::gui::pages::Project;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Output of earlier commands above a line for the next one
+pub fn console_view(ui: &mut egui::Ui, project: &mut Project) {
+    let input_id = ui.id().with("console_input");
+
+    ui.horizontal(|ui| {
+        ui.strong("Console");
+        ui.weak("Same commands as interactive mode, try `help`");
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.button("Close").clicked() {
+                project.console.open = false;
+            }
+            if ui.button("Clear").clicked() {
+                project.console.log.clear();
+            }
+        });
+    });
+
+    let input_height = ui.spacing().interact_size.y + ui.spacing().item_spacing.y;
+
+    egui::ScrollArea::vertical()
+        .max_height((ui.available_height() - input_height).max(0.0))
+        .auto_shrink([false, true])
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            ui.label(RichText::new(&project.console.log).monospace());
+        });
+
+    let console = &mut project.console;
+
+    let edit = ui.add(
+        egui::TextEdit::singleline(&mut console.input)
+            .id(input_id)
+            .font(egui::TextStyle::Monospace)
+            .hint_text("help")
+            .desired_width(f32::INFINITY),
+    );
+
+    if edit.has_focus() && !console.history.is_empty() {
+        let (up, down) = ui.input(|i| (i.key_pressed(Key::ArrowUp), i.key_pressed(Key::ArrowDown)));
+        let last = console.history.len() - 1;
+
+        if up {
+            let pos = console
+                .history_pos
+                .map_or(last, |pos| pos.saturating_sub(1));
+            console.history_pos = Some(pos);
+            console.input = console.history[pos].clone();
+        } else if down {
+            match console.history_pos {
+                Some(pos) if pos < last => {
+                    console.history_pos = Some(pos + 1);
+                    console.input = console.history[pos + 1].clone();
+                }
+                Some(_) => {
+                    console.history_pos = None;
+                    console.input.clear();
+                }
+                None => {}
+            }
+        }
+    }
+
+    if edit.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+        let input = std::mem::take(&mut console.input);
+        console.history_pos = None;
+
+        if !input.trim().is_empty() {
+            if console.history.last() != Some(&input) {
+                console.history.push(input.clone());
+            }
+            project.run_command(&input);
+        }
+
+        ui.memory_mut(|m| m.request_focus(input_id));
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
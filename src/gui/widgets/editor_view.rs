@@ -0,0 +1,411 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use egui::{
+    text::{CCursor, CCursorRange, LayoutJob},
+    Align, Align2, Color32, FontId, Sense, TextStyle, Vec2,
+};
+use egui_extras::syntax_highlighting::{self, CodeTheme};
+
+use crate::gui::{
+    pages::{self, Project, Selection},
+    palette::undo_shortcuts,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Font size of the lines inside a folded block, small enough to vanish
+const FOLDED_SIZE: f32 = 1.0;
+
+/// Room for the fold arrows, right of the line numbers
+const FOLD_WIDTH: f32 = 14.0;
+
+/// Graph text is always written and read as YAML, whatever the file is called
+const SYNTAX: &str = "yaml";
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Graph text, with what is wrong with it above and the bad lines marked
+///
+/// Selecting a node on the canvas scrolls to it here, and moving the cursor
+/// into a node's block selects it on the canvas
+pub fn editor_view(ui: &mut egui::Ui, project: &mut Project) {
+    let error = ui.visuals().error_fg_color;
+
+    if project.in_sync() {
+        ui.colored_label(Color32::from_rgb(70, 170, 90), "● In sync");
+    } else {
+        ui.colored_label(
+            error,
+            "● Out of sync, the canvas shows the last valid graph",
+        );
+        for diagnostic in &project.diagnostics {
+            let text = match diagnostic.line {
+                Some(line) => format!("Line {}: {}", line, diagnostic.message),
+                None => diagnostic.message.clone(),
+            };
+            ui.colored_label(error, text);
+        }
+    }
+
+    let edit_id = ui.id().with("graph_text");
+    let selection_id = edit_id.with("selection");
+
+    //-----------------------------------------------------------------------//
+    // Canvas to text
+
+    let selected = match &project.selection {
+        Selection::Node(node) => Some(node.clone()),
+        _ => None,
+    };
+    let selected_line = selected
+        .as_ref()
+        .and_then(|node| pages::node_line(&project.text, node));
+
+    let last_selected = ui.data(|d| d.get_temp::<Option<String>>(selection_id));
+    let mut jump = None;
+    if last_selected.as_ref() != Some(&selected) {
+        if let Some(line) = selected_line {
+            jump = Some(line);
+            unfold(project, line);
+        }
+    }
+
+    let foldable = blocks(&project.text);
+    // edits move blocks around, so drop folds that no longer start one
+    project
+        .folded
+        .retain(|line| foldable.iter().any(|(start, _)| start == line));
+
+    if let Some(line) = jump {
+        let index = char_index(&project.text, line);
+        if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), edit_id) {
+            state
+                .cursor
+                .set_char_range(Some(CCursorRange::one(CCursor::new(index))));
+            state.store(ui.ctx(), edit_id);
+        }
+    }
+
+    //-----------------------------------------------------------------------//
+    // Layout
+
+    let hidden: Vec<bool> = {
+        let mut hidden = vec![false; project.text.lines().count() + 1];
+        for (start, end) in &foldable {
+            if project.folded.contains(start) {
+                hidden[start + 1..*end].fill(true);
+            }
+        }
+        hidden
+    };
+
+    let bad_lines: Vec<usize> = project
+        .diagnostics
+        .iter()
+        .filter_map(|diagnostic| diagnostic.line)
+        .collect();
+    let error_marker = error.gamma_multiply(0.25);
+    let selected_marker = ui.visuals().selection.bg_fill.gamma_multiply(0.3);
+
+    let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+        let theme = CodeTheme::from_memory(ui.ctx());
+        let highlighted = syntax_highlighting::highlight(ui.ctx(), &theme, text, SYNTAX);
+
+        let mut job = mark_lines(highlighted, text, |line, format| {
+            if hidden.get(line).copied().unwrap_or(false) {
+                format.font_id = FontId::monospace(FOLDED_SIZE);
+                format.color = Color32::TRANSPARENT;
+            } else if bad_lines.contains(&(line + 1)) {
+                format.background = error_marker;
+            } else if selected_line == Some(line + 1) {
+                format.background = selected_marker;
+            }
+        });
+        job.wrap.max_width = wrap_width;
+        ui.fonts(|f| f.layout_job(job))
+    };
+
+    //-----------------------------------------------------------------------//
+    // Editor
+
+    let font = TextStyle::Monospace.resolve(ui.style());
+    let digits = project.text.lines().count().max(1).to_string().len();
+    let digit_width = ui.fonts(|f| f.glyph_width(&font, '0'));
+    let gutter_width = digits as f32 * digit_width + FOLD_WIDTH;
+
+    let mut toggled = None;
+
+    // before the text edit sees them, so its own undo doesn't fight ours
+    if ui.memory(|m| m.has_focus(edit_id)) {
+        undo_shortcuts(ui.ctx(), project);
+    }
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.horizontal_top(|ui| {
+            let (gutter, _) = ui.allocate_exact_size(Vec2::new(gutter_width, 0.0), Sense::hover());
+
+            let output = egui::TextEdit::multiline(&mut project.text)
+                .id(edit_id)
+                .code_editor()
+                .desired_width(f32::INFINITY)
+                .layouter(&mut layouter)
+                .show(ui);
+
+            if output.response.changed() {
+                project.update_graph();
+            }
+
+            let galley = &output.galley;
+            let painter = ui.painter();
+            let weak = ui.visuals().weak_text_color();
+
+            // first row of every line
+            let mut line = 0;
+            let mut starts_line = true;
+            for row in &galley.rows {
+                if starts_line && !hidden.get(line).copied().unwrap_or(false) {
+                    let y = output.galley_pos.y + row.rect.min.y;
+                    let color = if bad_lines.contains(&(line + 1)) {
+                        error
+                    } else {
+                        weak
+                    };
+                    painter.text(
+                        egui::pos2(gutter.right() - FOLD_WIDTH, y),
+                        Align2::RIGHT_TOP,
+                        (line + 1).to_string(),
+                        font.clone(),
+                        color,
+                    );
+
+                    if foldable.iter().any(|(start, _)| *start == line) {
+                        let rect = egui::Rect::from_min_size(
+                            egui::pos2(gutter.right() - FOLD_WIDTH, y),
+                            Vec2::new(FOLD_WIDTH, row.rect.height()),
+                        );
+                        let response =
+                            ui.interact(rect, edit_id.with(("fold", line)), Sense::click());
+                        let folded = hidden.get(line + 1).copied().unwrap_or(false);
+                        let arrow = if folded { "▸" } else { "▾" };
+                        let color = if response.hovered() {
+                            ui.visuals().strong_text_color()
+                        } else {
+                            weak
+                        };
+                        painter.text(
+                            rect.center(),
+                            Align2::CENTER_CENTER,
+                            arrow,
+                            font.clone(),
+                            color,
+                        );
+                        if folded {
+                            painter.text(
+                                output.galley_pos
+                                    + row.rect.right_top().to_vec2()
+                                    + Vec2::new(digit_width, 0.0),
+                                Align2::LEFT_TOP,
+                                "…",
+                                font.clone(),
+                                weak,
+                            );
+                        }
+                        if response.clicked() {
+                            toggled = Some(line);
+                        }
+                    }
+                }
+                starts_line = row.ends_with_newline;
+                if row.ends_with_newline {
+                    line += 1;
+                }
+            }
+
+            if let Some(line) = jump {
+                let index = char_index(&project.text, line);
+                let cursor = galley.from_ccursor(CCursor::new(index));
+                let rect = galley
+                    .pos_from_cursor(&cursor)
+                    .translate(output.galley_pos.to_vec2());
+                ui.scroll_to_rect(rect, Some(Align::Center));
+            }
+
+            //---------------------------------------------------------------//
+            // Text to canvas
+
+            if output.response.has_focus() {
+                if let Some(range) = output.cursor_range {
+                    let line = line_of(&project.text, range.primary.ccursor.index);
+                    if let Some(node) = node_at_line(project, line) {
+                        if selected.as_ref() != Some(&node) {
+                            project.selection = Selection::Node(node.clone());
+                            project.focus = Some(node);
+                        }
+                    }
+                }
+            }
+        });
+    });
+
+    if let Some(line) = toggled {
+        if !project.folded.remove(&line) {
+            project.folded.insert(line);
+        }
+    }
+
+    let selected = match &project.selection {
+        Selection::Node(node) => Some(node.clone()),
+        _ => None,
+    };
+    ui.data_mut(|d| d.insert_temp(selection_id, selected));
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Indentation of a line, counting list dashes, or None if it is blank
+fn indent(line: &str) -> Option<usize> {
+    let rest = line.trim_start();
+    if rest.is_empty() || rest.starts_with('#') {
+        return None;
+    }
+
+    let mut indent = line.len() - rest.len();
+    let mut rest = rest;
+    while let Some(inner) = rest.strip_prefix("- ") {
+        let inner_trimmed = inner.trim_start();
+        indent += rest.len() - inner_trimmed.len();
+        rest = inner_trimmed;
+    }
+    Some(indent)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Lines that can be folded, with the end of what they fold, both from 0
+///
+/// A block is everything indented further than its first line, or up to the
+/// closing bracket if the line ends with an opening one
+fn blocks(text: &str) -> Vec<(usize, usize)> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut res = vec![];
+
+    for (start, line) in lines.iter().enumerate() {
+        let Some(own) = indent(line) else {
+            continue;
+        };
+
+        let end = if line.trim_end().ends_with(['[', '{']) {
+            let mut depth = 0;
+            lines[start..]
+                .iter()
+                .position(|line| {
+                    for c in line.chars() {
+                        match c {
+                            '[' | '{' => depth += 1,
+                            ']' | '}' => depth -= 1,
+                            _ => {}
+                        }
+                    }
+                    depth <= 0
+                })
+                .map_or(lines.len(), |i| start + i + 1)
+        } else {
+            lines[start + 1..]
+                .iter()
+                .position(|line| indent(line).is_some_and(|indent| indent <= own))
+                .map_or(lines.len(), |i| start + 1 + i)
+        };
+
+        // trailing blank lines stay visible
+        let end = (start + 1..end)
+            .rev()
+            .find(|i| indent(lines[*i]).is_some())
+            .map_or(start + 1, |i| i + 1);
+
+        if end > start + 1 {
+            res.push((start, end));
+        }
+    }
+
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// Unfolds every block hiding `line`, which starts at 1
+fn unfold(project: &mut Project, line: usize) {
+    for (start, end) in blocks(&project.text) {
+        if start < line - 1 && line - 1 < end {
+            project.folded.remove(&start);
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Applies `mark` to the format of every line, counting from 0
+fn mark_lines(
+    job: LayoutJob,
+    text: &str,
+    mut mark: impl FnMut(usize, &mut egui::TextFormat),
+) -> LayoutJob {
+    let mut res = LayoutJob::default();
+    let mut line = 0;
+
+    for section in job.sections {
+        for part in text[section.byte_range].split_inclusive('\n') {
+            let mut format = section.format.clone();
+            mark(line, &mut format);
+            res.append(part, 0.0, format);
+            if part.ends_with('\n') {
+                line += 1;
+            }
+        }
+    }
+
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// Character index of the start of `line`, which starts at 1
+fn char_index(text: &str, line: usize) -> usize {
+    text.split_inclusive('\n')
+        .take(line - 1)
+        .map(|line| line.chars().count())
+        .sum()
+}
+
+//---------------------------------------------------------------------------//
+
+/// Line of a character index, starting at 1
+fn line_of(text: &str, index: usize) -> usize {
+    text.chars().take(index).filter(|c| *c == '\n').count() + 1
+}
+
+//---------------------------------------------------------------------------//
+
+/// Node whose definition `line` is part of
+fn node_at_line(project: &Project, line: usize) -> Option<String> {
+    let text = &project.text;
+    let lines: Vec<&str> = text.lines().collect();
+
+    project
+        .graphic
+        .keys()
+        .filter_map(|node| Some((pages::node_line(text, node)?, node)))
+        .filter(|(start, _)| *start <= line)
+        .max_by_key(|(start, _)| *start)
+        .filter(|(start, _)| {
+            // still inside its block
+            let own = indent(lines[start - 1]).unwrap_or(0);
+            lines
+                .iter()
+                .take(line - 1)
+                .skip(*start)
+                .all(|line| indent(line).is_none_or(|indent| indent > own))
+        })
+        .map(|(_, node)| node.clone())
+}
+
+///////////////////////////////////////////////////////////////////////////////
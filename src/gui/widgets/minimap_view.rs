@@ -0,0 +1,102 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use cs_240_library::data_structures::graphs::IGraph;
+use egui::{ahash::HashMap, Pos2, Rect, Sense, Shape, Stroke, Vec2};
+
+use crate::gui::pages::Project;
+
+///////////////////////////////////////////////////////////////////////////////
+
+const MINIMAP_SIZE: Vec2 = Vec2::new(180.0, 120.0);
+
+/// Gap between the minimap and the corner of the canvas
+const MINIMAP_MARGIN: f32 = 8.0;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// The whole layout in the bottom right corner of `canvas`, with the part
+/// in view outlined
+///
+/// Clicking or dragging on it centres the view there. `offset` is where the
+/// view transform starts from on screen
+pub fn minimap_view(
+    ui: &mut egui::Ui,
+    project: &mut Project,
+    rects: &HashMap<String, Rect>,
+    canvas: Rect,
+    offset: Vec2,
+) {
+    let Some(bounds) = rects.values().copied().reduce(|a, b| a.union(b)) else {
+        return;
+    };
+    let bounds = bounds.expand(20.0);
+
+    let map = Rect::from_min_size(
+        canvas.right_bottom() - MINIMAP_SIZE - Vec2::splat(MINIMAP_MARGIN),
+        MINIMAP_SIZE,
+    );
+    if !canvas.contains_rect(map) {
+        return;
+    }
+
+    let scale = (map.width() / bounds.width()).min(map.height() / bounds.height());
+    let to_map = |pos: Pos2| map.center() + (pos - bounds.center()) * scale;
+    let from_map = |pos: Pos2| bounds.center() + (pos - map.center()) / scale;
+
+    let response = ui.interact(map, ui.id().with("minimap"), Sense::click_and_drag());
+
+    if let Some(pointer) = response.interact_pointer_pos() {
+        if response.is_pointer_button_down_on() || response.clicked() {
+            let view = &mut project.view;
+            view.target = None;
+            view.transform.translation = canvas.center().to_vec2()
+                - offset
+                - view.transform.scaling * from_map(pointer).to_vec2();
+        }
+    }
+
+    let visuals = ui.visuals();
+    let painter = ui.painter_at(map);
+
+    painter.rect(
+        map,
+        4.0,
+        visuals.extreme_bg_color.gamma_multiply(0.9),
+        visuals.window_stroke,
+    );
+
+    let edge_stroke = Stroke::new(1.0, visuals.weak_text_color().gamma_multiply(0.5));
+    for (node, node_rect) in rects {
+        for adj in project.graph.get_adj(node) {
+            if let Some(other) = rects.get(&adj) {
+                painter.line_segment(
+                    [to_map(node_rect.center()), to_map(other.center())],
+                    edge_stroke,
+                );
+            }
+        }
+    }
+
+    let node_fill = visuals.text_color().gamma_multiply(0.6);
+    for node_rect in rects.values() {
+        let rect = Rect::from_min_max(to_map(node_rect.min), to_map(node_rect.max));
+        let rect = Rect::from_center_size(rect.center(), rect.size().max(Vec2::splat(2.0)));
+        painter.add(Shape::rect_filled(rect, 1.0, node_fill));
+    }
+
+    // what the canvas shows, in graph space
+    let view = project.view.transform;
+    let visible = Rect::from_min_max(
+        ((canvas.min.to_vec2() - offset - view.translation) / view.scaling).to_pos2(),
+        ((canvas.max.to_vec2() - offset - view.translation) / view.scaling).to_pos2(),
+    );
+    let viewport = Rect::from_min_max(to_map(visible.min), to_map(visible.max));
+    painter.rect(
+        viewport,
+        0.0,
+        visuals.selection.bg_fill.gamma_multiply(0.15),
+        Stroke::new(1.5, visuals.selection.stroke.color),
+    );
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -1,7 +1,14 @@
 ///////////////////////////////////////////////////////////////////////////////
 
+pub mod console_view;
+pub mod editor_view;
+pub mod minimap_view;
 pub mod modal_view;
 pub mod page_view;
+pub mod player_view;
+pub mod route_view;
+pub mod search_view;
+pub mod tab_bar;
 pub mod top_bar;
 
 ///////////////////////////////////////////////////////////////////////////////
@@ -1,13 +1,333 @@
 ///////////////////////////////////////////////////////////////////////////////
 
-use crate::gui::App;
+use std::path::PathBuf;
+
+use egui::{Align2, Key, Vec2};
+
+use crate::gui::{
+    modals::{ExportImage, Modal, Palette, PathInput},
+    pages::Project,
+    palette::{missing_argument, rank, Command},
+    App,
+};
 
 ///////////////////////////////////////////////////////////////////////////////
 
 pub fn modal_view(app: &mut App, ctx: &egui::Context) {
-    for modal in &mut app.modals {
-        todo!()
+    // taken out so the views can use the rest of the app
+    let mut modals = std::mem::take(&mut app.modals);
+
+    modals.retain_mut(|modal| match modal {
+        Modal::FindFile(input) => path_view(ctx, "Open Graph", "Open", input, |path| {
+            app.open_graph(path)
+        }),
+        Modal::SaveFile(input) => path_view(ctx, "Save Graph", "Save", input, |path| {
+            app.save_graph_to(path)
+        }),
+        Modal::ExportImage(export) => export_image_view(ctx, app.project(), export),
+        Modal::Palette(palette) => palette_view(ctx, app, palette),
+        Modal::CloseTab(id) => close_tab_view(ctx, app, *id),
+    });
+
+    // keep modals opened by the views above
+    modals.append(&mut app.modals);
+    app.modals = modals;
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Returns false once the modal should close
+fn path_view(
+    ctx: &egui::Context,
+    title: &str,
+    action: &str,
+    input: &mut PathInput,
+    mut submit: impl FnMut(PathBuf) -> anyhow::Result<()>,
+) -> bool {
+    let mut open = true;
+    let mut done = false;
+
+    egui::Window::new(title)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Path");
+                ui.text_edit_singleline(&mut input.path);
+            });
+
+            if let Some(error) = &input.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+
+            ui.separator();
+
+            if ui.button(action).clicked() {
+                match submit(PathBuf::from(&input.path)) {
+                    Ok(()) => done = true,
+                    Err(err) => input.error = Some(err.to_string()),
+                }
+            }
+        });
+
+    open && !done
+}
+
+//---------------------------------------------------------------------------//
+
+/// Returns false once the modal should close
+fn export_image_view(
+    ctx: &egui::Context,
+    project: Option<&Project>,
+    export: &mut ExportImage,
+) -> bool {
+    let mut open = true;
+    let mut done = false;
+
+    egui::Window::new("Export Image")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Path");
+                ui.text_edit_singleline(&mut export.path);
+            });
+
+            ui.add(egui::Slider::new(&mut export.scale, 0.25..=8.0).text("Scale"));
+            ui.label(format!("{:.0} DPI", export.scale * 96.0));
+
+            if let Some(error) = &export.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+
+            ui.separator();
+
+            if ui.button("Export").clicked() {
+                let res = match project {
+                    Some(project) => project.export_image(&export.path, export.scale),
+                    None => Err(anyhow::Error::msg("No project open")),
+                };
+
+                match res {
+                    Ok(()) => done = true,
+                    Err(err) => export.error = Some(err.to_string()),
+                }
+            }
+        });
+
+    open && !done
+}
+
+//---------------------------------------------------------------------------//
+
+/// Returns false once the modal should close
+fn close_tab_view(ctx: &egui::Context, app: &mut App, id: u64) -> bool {
+    let Some(index) = app.tabs.iter().position(|project| project.id == id) else {
+        // closed some other way in the meantime
+        return false;
+    };
+    let project = &mut app.tabs[index];
+
+    let mut open = true;
+    let mut done = false;
+    let mut save_as = false;
+    let mut cancelled = false;
+    let mut error = None;
+
+    egui::Window::new("Unsaved Changes")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "{} has changes that haven't been saved.",
+                project.name()
+            ));
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    if project.path.is_some() {
+                        match project.save() {
+                            Ok(()) => done = true,
+                            Err(err) => error = Some(err.to_string()),
+                        }
+                    } else {
+                        save_as = true;
+                    }
+                }
+                if ui.button("Don't Save").clicked() {
+                    done = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancelled = true;
+                }
+            });
+        });
+
+    if save_as {
+        // closing is up to the user again once it has a file
+        app.current = index;
+        app.save_graph_as();
+        return false;
     }
+
+    if let Some(error) = error {
+        // the save dialog saves the current tab
+        app.current = index;
+        app.modals.push(Modal::SaveFile(PathInput {
+            path: Default::default(),
+            error: Some(error),
+        }));
+        return false;
+    }
+
+    if done {
+        app.remove_tab(index);
+    }
+
+    open && !done && !cancelled
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Returns false once the modal should close
+fn palette_view(ctx: &egui::Context, app: &mut App, palette: &mut Palette) -> bool {
+    if ctx.input(|i| i.key_pressed(Key::Escape)) {
+        return false;
+    }
+
+    let has_project = app.project().is_some();
+    let (up, down, enter) = ctx.input(|i| {
+        (
+            i.key_pressed(Key::ArrowUp),
+            i.key_pressed(Key::ArrowDown),
+            i.key_pressed(Key::Enter),
+        )
+    });
+
+    let mut chosen = None;
+    let mut run = None;
+    let mut back = false;
+
+    egui::Window::new("Commands")
+        .collapsible(false)
+        .resizable(false)
+        .title_bar(false)
+        .anchor(Align2::CENTER_TOP, Vec2::new(0.0, 48.0))
+        .default_width(420.0)
+        .show(ctx, |ui| match &mut palette.pending {
+            Some((command, args)) => {
+                ui.strong(command.label());
+                if let Some(desc) = command.description() {
+                    ui.weak(desc);
+                }
+
+                let patterns = command.arguments();
+                egui::Grid::new("palette_arguments")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for (i, (pattern, arg)) in patterns.iter().zip(args.iter_mut()).enumerate()
+                        {
+                            ui.label(pattern);
+                            ui.add(egui::TextEdit::singleline(arg).id(argument_id(i)));
+                            ui.end_row();
+                        }
+                    });
+
+                let ready = !missing_argument(&patterns, args);
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(ready, egui::Button::new("Run")).clicked() || (ready && enter)
+                    {
+                        run = Some((command.clone(), args.clone()));
+                    }
+                    if ui.button("Back").clicked() {
+                        back = true;
+                    }
+                });
+            }
+            None => {
+                let edit = ui.add(
+                    egui::TextEdit::singleline(&mut palette.query)
+                        .hint_text("Type a command")
+                        .desired_width(f32::INFINITY),
+                );
+                edit.request_focus();
+                if edit.changed() {
+                    palette.selected = 0;
+                }
+
+                let mut commands: Vec<(usize, Command)> = Command::all()
+                    .into_iter()
+                    .filter(|command| has_project || !command.needs_project())
+                    .map(|command| (rank(&palette.query, &command.label()), command))
+                    .collect();
+                // stable, so ties keep the order from `Command::all`
+                commands.sort_by_key(|(rank, _)| *rank);
+                commands.truncate(12);
+
+                if commands.is_empty() {
+                    ui.weak("No matching commands");
+                    return;
+                }
+
+                if down {
+                    palette.selected = (palette.selected + 1).min(commands.len() - 1);
+                }
+                if up {
+                    palette.selected = palette.selected.saturating_sub(1);
+                }
+                palette.selected = palette.selected.min(commands.len() - 1);
+
+                ui.separator();
+
+                for (i, (_, command)) in commands.into_iter().enumerate() {
+                    let mut row = ui.selectable_label(i == palette.selected, command.label());
+                    if let Some(desc) = command.description() {
+                        row = row.on_hover_text(desc);
+                    }
+                    if row.clicked() || (enter && i == palette.selected) {
+                        chosen = Some(command);
+                    }
+                }
+            }
+        });
+
+    if back {
+        palette.pending = None;
+    }
+
+    if let Some(command) = chosen {
+        let arguments = command.arguments();
+        if arguments.is_empty() {
+            run = Some((command, vec![]));
+        } else {
+            ctx.memory_mut(|m| m.request_focus(argument_id(0)));
+            palette.pending = Some((command, vec![String::new(); arguments.len()]));
+        }
+    }
+
+    match run {
+        Some((command, args)) => {
+            app.run_command(ctx, &command, &args);
+            false
+        }
+        None => true,
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+fn argument_id(i: usize) -> egui::Id {
+    egui::Id::new(("palette_argument", i))
 }
 
 ///////////////////////////////////////////////////////////////////////////////
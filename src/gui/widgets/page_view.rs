@@ -1,22 +1,41 @@
 ///////////////////////////////////////////////////////////////////////////////
 
-use cs_240_library::data_structures::graphs::{
-    directed_graph::DirectedGraph, undirected_graph::UndirectedGraph,
-    weighted_graph::WeightedGraph, IGraph,
+use std::sync::Arc;
+
+use cs_240_library::data_structures::graphs::{IDefiniteGraph, IGraph, IWeightedGraph};
+use egui::{
+    ahash::{HashMap, HashSet},
+    emath::TSTransform,
+    epaint::{CubicBezierShape, QuadraticBezierShape},
+    Color32, FontId, Key, Pos2, Rect, Shape, Stroke, TextStyle, Vec2,
 };
-use egui::{ahash::HashMap, emath::TSTransform, Color32, LayerId, Pos2, Stroke, Vec2};
 
-use crate::gui::{
-    pages::{PanZoom, Project},
-    App,
+use crate::{
+    document::GraphKind,
+    graphs,
+    gui::{
+        pages::{Fit, Project, Selection},
+        widgets::{
+            console_view::console_view,
+            editor_view::editor_view,
+            minimap_view::minimap_view,
+            player_view::{player_view, state_color},
+            route_view::{route_view, ROUTE_COLOR},
+            search_view::{search_bar, SEARCH_COLOR},
+        },
+        App,
+    },
+    layout::{self, layered, smacof, spring},
+    render::{self, EdgeCurve},
+    traversal::NodeState,
 };
 
 ///////////////////////////////////////////////////////////////////////////////
 
 pub fn page_view(app: &mut App, ctx: &egui::Context) {
-    match &mut app.active {
-        crate::gui::pages::Page::Blank => blank_view(ctx),
-        crate::gui::pages::Page::Project(project) => project_view(ctx, project),
+    match app.project_mut() {
+        Some(project) => project_view(ctx, project),
+        None => blank_view(ctx),
     }
 }
 
@@ -33,121 +52,1121 @@ pub fn blank_view(ctx: &egui::Context) {
 ///////////////////////////////////////////////////////////////////////////////
 
 pub fn project_view(ctx: &egui::Context, project: &mut Project) {
+    let dt = ctx.input(|input| input.stable_dt).min(0.1);
+    if project.animate(dt) | project.player.tick(dt) | project.view.animate(dt) {
+        ctx.request_repaint();
+    }
+
     egui::SidePanel::left("left_panel")
         .default_width(320.0)
         .min_width(240.0)
         .show(ctx, |ui| {
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                if ui.code_editor(&mut project.text).changed() {
-                    project.update_graph();
+            egui::CollapsingHeader::new("Document").show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Title");
+                    if ui.text_edit_singleline(&mut project.info.title).changed() {
+                        project.dirty = true;
+                        project.commit(true);
+                    }
+                });
+                ui.label("Description");
+                if ui
+                    .text_edit_multiline(&mut project.info.description)
+                    .changed()
+                {
+                    project.dirty = true;
+                    project.commit(true);
                 }
             });
+
+            egui::CollapsingHeader::new("Layout").show(ui, |ui| {
+                layout_view(ui, project);
+            });
+
+            egui::CollapsingHeader::new("Traversal").show(ui, |ui| {
+                player_view(ui, project);
+            });
+
+            egui::CollapsingHeader::new("Route").show(ui, |ui| {
+                route_view(ui, project);
+            });
+
+            ui.separator();
+
+            editor_view(ui, project);
         });
 
+    if project.console.open {
+        egui::TopBottomPanel::bottom("console")
+            .resizable(true)
+            .default_height(180.0)
+            .min_height(80.0)
+            .show(ctx, |ui| {
+                console_view(ui, project);
+            });
+    }
+
     egui::CentralPanel::default().show(ctx, |ui| {
-        pan_zoom_view(&mut project.view, ui, &project.graphic, &project.graph);
+        ui.horizontal(|ui| {
+            if ui.button("Re-layout").clicked() {
+                project.relayout();
+            }
+            ui.checkbox(&mut project.auto_layout, "Re-layout on edit");
+            if ui
+                .add_enabled(!project.pinned.is_empty(), egui::Button::new("Unpin all"))
+                .clicked()
+            {
+                project.unpin_all();
+            }
+            ui.toggle_value(&mut project.console.open, "Console");
+            if let Some(job) = &project.job {
+                ui.separator();
+                ui.spinner();
+                ui.add(
+                    egui::ProgressBar::new(job.progress())
+                        .desired_width(120.0)
+                        .show_percentage(),
+                );
+                if ui.button("Cancel").clicked() {
+                    project.cancel_layout();
+                }
+            }
+        });
+        view_bar(ui, project);
+        search_bar(ui, project);
+        ui.add_enabled_ui(project.in_sync(), |ui| selection_bar(ui, project));
+        pan_zoom_view(ui, project);
     });
 }
 
+//---------------------------------------------------------------------------//
+
+/// Framing and zoom of the canvas
+fn view_bar(ui: &mut egui::Ui, project: &mut Project) {
+    ui.horizontal(|ui| {
+        if ui.button("Fit all").clicked() {
+            project.fit = Some(Fit::All);
+        }
+        if ui
+            .add_enabled(
+                project.selection != Selection::None,
+                egui::Button::new("Fit selection"),
+            )
+            .clicked()
+        {
+            project.fit = Some(Fit::Selection);
+        }
+        if ui.button("Reset view").clicked() {
+            project.view.animate_to(TSTransform::default());
+        }
+
+        let view = &project.view;
+        let mut percent = view.target.unwrap_or(view.transform).scaling * 100.0;
+        let zoom = ui.add(
+            egui::DragValue::new(&mut percent)
+                .clamp_range(MIN_ZOOM * 100.0..=MAX_ZOOM * 100.0)
+                .speed(1.0)
+                .max_decimals(0)
+                .suffix("%"),
+        );
+        if zoom.changed() {
+            project.fit = Some(Fit::Scale(percent / 100.0));
+        }
+        ui.menu_button("Zoom", |ui| {
+            for preset in ZOOM_PRESETS {
+                if ui.button(format!("{}%", preset)).clicked() {
+                    project.fit = Some(Fit::Scale(preset / 100.0));
+                    ui.close_menu();
+                }
+            }
+        });
+
+        ui.toggle_value(&mut project.minimap, "Minimap");
+    });
+}
+
+//---------------------------------------------------------------------------//
+
+/// Layout method and its settings, used on the next re-layout
+fn layout_view(ui: &mut egui::Ui, project: &mut Project) {
+    egui::ComboBox::from_label("Method")
+        .selected_text(project.layout.method.name())
+        .show_ui(ui, |ui| {
+            for method in layout::Method::ALL {
+                ui.selectable_value(&mut project.layout.method, method, method.name());
+            }
+        });
+
+    match project.layout.method {
+        layout::Method::Auto => {
+            ui.label("Layered for DAGs");
+            layered_params_view(ui, &mut project.layout.layered);
+            ui.label("Spring otherwise");
+            spring_params_view(ui, &mut project.layout.spring);
+        }
+        layout::Method::Layered => layered_params_view(ui, &mut project.layout.layered),
+        layout::Method::Spring => spring_params_view(ui, &mut project.layout.spring),
+        layout::Method::Stress => smacof_params_view(ui, &mut project.layout.smacof),
+        layout::Method::Radial => {
+            root_view(ui, project);
+            ui.add(egui::Slider::new(&mut project.layout.spacing, 20.0..=400.0).text("Spacing"));
+        }
+        layout::Method::Circular | layout::Method::Grid | layout::Method::Bipartite => {
+            ui.add(egui::Slider::new(&mut project.layout.spacing, 20.0..=400.0).text("Spacing"));
+        }
+    }
+
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        match project.stress {
+            Some(stress) => ui.label(format!("Stress: {:.3}", stress)),
+            None => ui.label("Stress: graph too large to measure"),
+        }
+        .on_hover_text("How far node distances are from shortest path distances, lower is better");
+
+        if ui.small_button("Measure").clicked() {
+            project.update_stress();
+        }
+    });
+
+    if ui
+        .button("Refine with stress majorization")
+        .on_hover_text("Improves the current positions, pinned nodes stay put")
+        .clicked()
+    {
+        project.smacof();
+    }
+
+    if ui.button("Defaults").clicked() {
+        project.layout = Default::default();
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Picks the centre of the radial layout
+fn root_view(ui: &mut egui::Ui, project: &mut Project) {
+    let mut nodes: Vec<String> = project.graphic.keys().cloned().collect();
+    nodes.sort();
+
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_label("Root")
+            .selected_text(project.layout.root.as_deref().unwrap_or("Automatic"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut project.layout.root, None, "Automatic");
+                for node in nodes {
+                    ui.selectable_value(&mut project.layout.root, Some(node.clone()), node);
+                }
+            });
+
+        if let Selection::Node(node) = &project.selection {
+            if ui.button("Use selected").clicked() {
+                project.layout.root = Some(node.clone());
+            }
+        }
+    });
+}
+
+//---------------------------------------------------------------------------//
+
+/// Settings for the layered layout
+fn layered_params_view(ui: &mut egui::Ui, params: &mut layered::Params) {
+    egui::Grid::new("layered_params")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Direction");
+            ui.horizontal(|ui| {
+                ui.selectable_value(
+                    &mut params.direction,
+                    layered::Direction::TopDown,
+                    "Top down",
+                );
+                ui.selectable_value(
+                    &mut params.direction,
+                    layered::Direction::LeftRight,
+                    "Left to right",
+                );
+            });
+            ui.end_row();
+
+            ui.label("Layer gap");
+            ui.add(egui::Slider::new(&mut params.layer_gap, 10.0..=300.0));
+            ui.end_row();
+
+            ui.label("Node gap");
+            ui.add(egui::Slider::new(&mut params.node_gap, 5.0..=200.0));
+            ui.end_row();
+
+            ui.label("Sweeps")
+                .on_hover_text("More sweeps can untangle more edge crossings");
+            ui.add(egui::Slider::new(&mut params.sweeps, 0..=100));
+            ui.end_row();
+        });
+}
+
+//---------------------------------------------------------------------------//
+
+/// Sliders for the force-directed layout
+fn spring_params_view(ui: &mut egui::Ui, params: &mut spring::Params) {
+    egui::Grid::new("spring_params")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Edge length");
+            ui.add(egui::Slider::new(&mut params.edge_length, 20.0..=400.0));
+            ui.end_row();
+
+            ui.label("Repulsion");
+            ui.add(egui::Slider::new(&mut params.repulsion, 0.1..=10.0).logarithmic(true));
+            ui.end_row();
+
+            ui.label("Gravity");
+            ui.add(egui::Slider::new(&mut params.gravity, 0.0..=1.0));
+            ui.end_row();
+
+            ui.label("Accuracy (θ)")
+                .on_hover_text("0 is exact, higher is faster for large graphs");
+            ui.add(egui::Slider::new(&mut params.theta, 0.0..=2.0));
+            ui.end_row();
+
+            ui.label("Cooling");
+            ui.add(egui::Slider::new(&mut params.cooling, 0.8..=0.999));
+            ui.end_row();
+
+            ui.label("Max steps");
+            ui.add(egui::Slider::new(&mut params.max_steps, 10..=5000).logarithmic(true));
+            ui.end_row();
+
+            ui.label("Tolerance")
+                .on_hover_text("Stops once no node moves further than this in a step");
+            ui.add(egui::Slider::new(&mut params.tolerance, 0.01..=10.0).logarithmic(true));
+            ui.end_row();
+        });
+}
+
+//---------------------------------------------------------------------------//
+
+/// Sliders for stress majorization
+fn smacof_params_view(ui: &mut egui::Ui, params: &mut smacof::Params) {
+    egui::Grid::new("smacof_params")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Edge length");
+            ui.add(egui::Slider::new(&mut params.edge_length, 20.0..=400.0));
+            ui.end_row();
+
+            ui.label("Max iterations");
+            ui.add(egui::Slider::new(&mut params.max_iterations, 10..=2000).logarithmic(true));
+            ui.end_row();
+
+            ui.label("Tolerance").on_hover_text(
+                "Stops once an iteration lowers the stress by less than this fraction",
+            );
+            ui.add(egui::Slider::new(&mut params.tolerance, 1e-6..=1e-1).logarithmic(true));
+            ui.end_row();
+        });
+}
+
+//---------------------------------------------------------------------------//
+
+/// Shows the selected node or edge, with an editor for edge weights
+fn selection_bar(ui: &mut egui::Ui, project: &mut Project) {
+    ui.horizontal(|ui| match project.selection.clone() {
+        Selection::None => {
+            ui.label("Nothing selected");
+        }
+        Selection::Node(node) => {
+            ui.label(format!("Node {}", node));
+            if ui.button("Delete").clicked() {
+                project.remove_selection();
+            }
+        }
+        Selection::Edge(from, to) => {
+            let arrow = if project.kind == GraphKind::Undirected {
+                "—"
+            } else {
+                "→"
+            };
+            ui.label(format!("Edge {} {} {}", from, arrow, to));
+
+            let weight = project
+                .graph
+                .get_adj_weighted(&from)
+                .into_iter()
+                .find(|(adj, _)| *adj == to)
+                .map(|(_, weight)| weight);
+
+            // directed and undirected files have nowhere to keep weights
+            if let Some(mut weight) = weight.filter(|_| project.kind == GraphKind::Weighted) {
+                ui.label("Weight");
+                if ui.add(egui::DragValue::new(&mut weight)).changed() {
+                    project.set_edge(&from, &to, weight);
+                }
+            }
+
+            if ui.button("Delete").clicked() {
+                project.remove_selection();
+            }
+        }
+    });
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Canvas edits, applied once the frame is drawn
+enum Edit {
+    Add(Pos2),
+    Connect(String, String),
+    Rename(String, String),
+    Remove(Selection),
+}
+
+//---------------------------------------------------------------------------//
+
+/// What the current drag on the canvas is doing
+#[derive(Clone)]
+enum Drag {
+    View,
+    Move(String),
+    Connect(String),
+}
+
+//---------------------------------------------------------------------------//
+
+/// Paints shapes given in graph space through the view transform
+struct Canvas<'a> {
+    painter: &'a egui::Painter,
+    transform: TSTransform,
+}
+
+impl Canvas<'_> {
+    fn add(&self, shape: impl Into<Shape>) {
+        let mut shape = shape.into();
+        shape.transform(self.transform);
+        self.painter.add(shape);
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// What the canvas works out from the graph, kept until the graph changes
+struct Scene {
+    revision: u64,
+    font: FontId,
+    /// Size of each node's box, fitting its label
+    sizes: HashMap<String, Vec2>,
+    /// Undirected edges are only in `edges` once and have no arrowheads
+    directed: bool,
+    edges: Vec<SceneEdge>,
+}
+
+//...........................................................................//
+
+struct SceneEdge {
+    from: String,
+    to: String,
+    weight: i32,
+    /// There is an edge back too, so both bend apart
+    twin: bool,
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 
-fn pan_zoom_view(
-    view: &mut PanZoom,
-    ui: &mut egui::Ui,
-    graph: &HashMap<String, Pos2>,
-    adj: &WeightedGraph<String, i32>,
+const NODE_MARGIN: f32 = 8.0;
+
+/// Room left around the nodes when fitting them into view
+const FIT_MARGIN: f32 = 40.0;
+
+const MIN_ZOOM: f32 = 0.05;
+const MAX_ZOOM: f32 = 8.0;
+
+/// Zoom levels in percent offered in the zoom menu
+const ZOOM_PRESETS: [f32; 7] = [25.0, 50.0, 75.0, 100.0, 150.0, 200.0, 400.0];
+
+//---------------------------------------------------------------------------//
+
+/// View transform that puts `point` in the middle of `canvas`
+fn centered(point: Pos2, scaling: f32, canvas: Rect, offset: Vec2) -> TSTransform {
+    TSTransform {
+        scaling,
+        translation: canvas.center().to_vec2() - offset - scaling * point.to_vec2(),
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Size of the box around a node's label
+fn node_size(ui: &egui::Ui, node: &str, font: &FontId) -> Vec2 {
+    let size = ui
+        .painter()
+        .layout_no_wrap(node.to_owned(), font.clone(), ui.visuals().text_color())
+        .size();
+    size + Vec2::splat(2.0 * NODE_MARGIN)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Scene of the project's graph, only worked out again once it has changed
+fn scene(ui: &egui::Ui, id: egui::Id, project: &Project, font: &FontId) -> Arc<Scene> {
+    let cached: Option<Arc<Scene>> = ui.data(|d| d.get_temp(id));
+    if let Some(scene) = cached {
+        if scene.revision == project.revision && scene.font == *font {
+            return scene;
+        }
+    }
+
+    let nodes = project.graph.get_all();
+    let sizes = nodes
+        .iter()
+        .map(|node| (node.clone(), node_size(ui, node, font)))
+        .collect();
+
+    let directed = project.kind != GraphKind::Undirected;
+    let edges = if directed {
+        graphs::edges(&project.graph)
+    } else {
+        graphs::undirected_edges(&project.graph)
+    };
+    let edges = edges
+        .into_iter()
+        .map(|(from, to, weight)| SceneEdge {
+            twin: directed && render::has_edge(&project.graph, &to, &from),
+            from,
+            to,
+            weight,
+        })
+        .collect();
+
+    let scene = Arc::new(Scene {
+        revision: project.revision,
+        font: font.clone(),
+        sizes,
+        directed,
+        edges,
+    });
+    ui.data_mut(|d| d.insert_temp(id, scene.clone()));
+    scene
+}
+
+//---------------------------------------------------------------------------//
+
+/// Boxes of every node that has a position
+fn node_rects(
+    ui: &egui::Ui,
+    scene: &Scene,
+    project: &Project,
+    font: &FontId,
+) -> HashMap<String, Rect> {
+    project
+        .graphic
+        .iter()
+        .map(|(node, pos)| {
+            let size = match scene.sizes.get(node) {
+                Some(size) => *size,
+                None => node_size(ui, node, font),
+            };
+            (node.clone(), Rect::from_min_size(*pos, size))
+        })
+        .collect()
+}
+
+//---------------------------------------------------------------------------//
+
+/// Topmost node under `point`
+fn node_at(rects: &HashMap<String, Rect>, point: Pos2) -> Option<String> {
+    rects
+        .iter()
+        .filter(|(_, rect)| rect.contains(point))
+        .last()
+        .map(|(node, _)| node.clone())
+}
+
+//---------------------------------------------------------------------------//
+
+/// Area an edge between two boxes can take up, with room for bends and
+/// self-loops
+fn edge_bounds(from: Rect, to: Rect) -> Rect {
+    from.union(to).expand(40.0)
+}
+
+//---------------------------------------------------------------------------//
+
+fn edge_curve(rects: &HashMap<String, Rect>, edge: &SceneEdge) -> Option<EdgeCurve> {
+    Some(render::edge_curve(
+        *rects.get(&edge.from)?,
+        *rects.get(&edge.to)?,
+        edge.from == edge.to,
+        edge.twin,
+    ))
+}
+
+//---------------------------------------------------------------------------//
+
+fn segment_distance(point: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let t = ((point - a).dot(ab) / ab.length_sq().max(f32::EPSILON)).clamp(0.0, 1.0);
+    point.distance(a + ab * t)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Edge closest to `point`, if any is within `max_dist`
+fn edge_at(
+    scene: &Scene,
+    rects: &HashMap<String, Rect>,
+    point: Pos2,
+    max_dist: f32,
+) -> Option<(String, String)> {
+    let mut best = None;
+    let mut best_dist = max_dist;
+
+    for edge in &scene.edges {
+        let (Some(from), Some(to)) = (rects.get(&edge.from), rects.get(&edge.to)) else {
+            continue;
+        };
+        if !edge_bounds(*from, *to).expand(max_dist).contains(point) {
+            continue;
+        }
+        let Some(curve) = edge_curve(rects, edge) else {
+            continue;
+        };
+
+        let points: Vec<_> = (0..=16).map(|i| curve.sample(i as f32 / 16.0)).collect();
+        for segment in points.windows(2) {
+            let dist = segment_distance(point, segment[0], segment[1]);
+            if dist < best_dist {
+                best_dist = dist;
+                best = Some((edge.from.clone(), edge.to.clone()));
+            }
+        }
+    }
+
+    best
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Draws an edge with its weight label, and its arrowhead if `directed`
+fn paint_edge(
+    canvas: &Canvas,
+    curve: &EdgeCurve,
+    directed: bool,
+    weight: i32,
+    stroke: Stroke,
+    fill: Color32,
 ) {
+    match curve {
+        EdgeCurve::Bend(points) => canvas.add(QuadraticBezierShape::from_points_stroke(
+            *points,
+            false,
+            Color32::TRANSPARENT,
+            stroke,
+        )),
+        EdgeCurve::Loop(points) => canvas.add(CubicBezierShape::from_points_stroke(
+            *points,
+            false,
+            Color32::TRANSPARENT,
+            stroke,
+        )),
+    };
+
+    let (tip, dir) = curve.tip();
+    if directed && dir.length() > 0.0 {
+        let back = dir.normalized() * 10.0;
+        let side = back.rot90() * 0.4;
+        canvas.add(Shape::convex_polygon(
+            vec![tip, tip - back + side, tip - back - side],
+            stroke.color,
+            Stroke::NONE,
+        ));
+    }
+
+    let mid = curve.sample(0.5);
+    let galley =
+        canvas
+            .painter
+            .layout_no_wrap(weight.to_string(), FontId::proportional(12.0), stroke.color);
+    let rect = Rect::from_center_size(mid, galley.size());
+    canvas.add(Shape::rect_filled(rect.expand(2.0), 2.0, fill));
+    canvas.add(Shape::galley(rect.min, galley, stroke.color));
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+fn pan_zoom_view(ui: &mut egui::Ui, project: &mut Project) {
     ui.label(
         "Pan, zoom in, and zoom out with scrolling. \
-               Double click on the background to reset.",
+               Double click on the background to add a node. \
+               Drag nodes to move and pin them, shift drag from one node to another to connect them. \
+               Click to select, Delete to remove, double click a node to rename it.",
     );
     ui.separator();
 
     let (id, rect) = ui.allocate_space(ui.available_size());
     let response = ui.interact(rect, id, egui::Sense::click_and_drag());
-    // Allow dragging the background as well.
-    if response.dragged() {
-        view.transform.translation += response.drag_delta();
-    }
 
-    // Plot-like reset
-    if response.double_clicked() {
-        view.transform = TSTransform::default();
-    }
-
-    let transform =
-        TSTransform::from_translation(ui.min_rect().left_top().to_vec2()) * view.transform;
+    // where the view transform starts from on screen
+    let offset = ui.min_rect().left_top().to_vec2();
+    let transform = TSTransform::from_translation(offset) * project.view.transform;
 
     if let Some(pointer) = ui.ctx().input(|i| i.pointer.hover_pos()) {
-        // Note: doesn't catch zooming / panning if a button in this PanZoom container is hovered.
         if response.hovered() {
             let pointer_in_layer = transform.inverse() * pointer;
             let zoom_delta = ui.ctx().input(|i| i.zoom_delta());
             let pan_delta = ui.ctx().input(|i| i.smooth_scroll_delta);
 
+            if zoom_delta != 1.0 || pan_delta != Vec2::ZERO {
+                project.view.target = None;
+            }
+
             // Zoom in on pointer:
-            view.transform = view.transform
+            project.view.transform = project.view.transform
                 * TSTransform::from_translation(pointer_in_layer.to_vec2())
                 * TSTransform::from_scaling(zoom_delta)
                 * TSTransform::from_translation(-pointer_in_layer.to_vec2());
 
             // Pan:
-            view.transform = TSTransform::from_translation(pan_delta) * view.transform;
+            project.view.transform =
+                TSTransform::from_translation(pan_delta) * project.view.transform;
+        }
+    }
+
+    let font = TextStyle::Body.resolve(ui.style());
+    let scene = scene(ui, id.with("scene"), project, &font);
+    let mut rects = node_rects(ui, &scene, project, &font);
+
+    if let Some(node) = project.focus.take() {
+        if let Some(node_rect) = rects.get(&node) {
+            let scaling = project.view.transform.scaling;
+            project
+                .view
+                .animate_to(centered(node_rect.center(), scaling, rect, offset));
+        }
+    }
+
+    if let Some(fit) = project.fit.take() {
+        let bounds = match (&fit, &project.selection) {
+            (Fit::All, _) => rects.values().copied().reduce(|a, b| a.union(b)),
+            (Fit::Selection, Selection::Node(node)) => rects.get(node).copied(),
+            (Fit::Selection, Selection::Edge(from, to)) => {
+                rects.get(from).zip(rects.get(to)).map(|(a, b)| a.union(*b))
+            }
+            _ => None,
+        };
+
+        let target = match fit {
+            Fit::Scale(scaling) => {
+                // keeps the middle of the canvas where it is
+                let view = project.view.target.unwrap_or(project.view.transform);
+                let middle = view.inverse() * (rect.center() - offset);
+                Some(centered(middle, scaling, rect, offset))
+            }
+            _ => bounds.map(|bounds| {
+                let bounds = bounds.expand(FIT_MARGIN);
+                let scaling = (rect.width() / bounds.width())
+                    .min(rect.height() / bounds.height())
+                    .clamp(MIN_ZOOM, MAX_ZOOM);
+                centered(bounds.center(), scaling, rect, offset)
+            }),
+        };
+        if let Some(target) = target {
+            project.view.animate_to(target);
+        }
+    }
+
+    let to_layer = |pos: Pos2| transform.inverse() * pos;
+    let hovered = ui
+        .input(|i| i.pointer.hover_pos())
+        .filter(|_| response.hovered())
+        .and_then(|pointer| node_at(&rects, to_layer(pointer)));
+
+    // node being renamed and the new name so far
+    let rename_id = id.with("rename");
+    let mut renaming: Option<(String, String)> = ui.data(|d| d.get_temp(rename_id));
+
+    let drag_id = id.with("drag");
+    let mut drag: Option<Drag> = ui.data(|d| d.get_temp(drag_id));
+
+    // node the context menu was opened on, and where
+    let menu_id = id.with("menu");
+
+    let mut edits = vec![];
+
+    //-----------------------------------------------------------------------//
+    // Dragging
+
+    if response.drag_started() {
+        let origin = ui.input(|i| i.pointer.press_origin());
+        drag = match origin.and_then(|origin| node_at(&rects, to_layer(origin))) {
+            // nodes can't be moved or connected on a graph the text no
+            // longer matches
+            Some(_) if !project.in_sync() => None,
+            Some(node) if ui.input(|i| i.modifiers.shift) => Some(Drag::Connect(node)),
+            Some(node) => Some(Drag::Move(node)),
+            None => Some(Drag::View),
+        };
+    }
+
+    if response.dragged() {
+        match &drag {
+            Some(Drag::View) => {
+                project.view.target = None;
+                project.view.transform.translation += response.drag_delta();
+            }
+            Some(Drag::Move(node)) => {
+                let delta = response.drag_delta() / transform.scaling;
+                if let Some(pos) = project.graphic.get_mut(node) {
+                    *pos += delta;
+                }
+                if let Some(rect) = rects.get_mut(node) {
+                    *rect = rect.translate(delta);
+                }
+                project.pinned.insert(node.clone());
+                project.dirty = true;
+            }
+            Some(Drag::Connect(_)) | None => {}
+        }
+    }
+
+    let pointer = ui.input(|i| i.pointer.interact_pos());
+
+    if ui.input(|i| i.pointer.any_released()) {
+        match drag.take() {
+            Some(Drag::Connect(from)) => {
+                let target = pointer.and_then(|pointer| node_at(&rects, to_layer(pointer)));
+                if let Some(to) = target.filter(|to| *to != from) {
+                    edits.push(Edit::Connect(from, to));
+                }
+            }
+            Some(Drag::Move(_)) => project.commit(false),
+            Some(Drag::View) | None => {}
+        }
+    }
+
+    //-----------------------------------------------------------------------//
+    // Clicking
+
+    if let Some(pointer) = response.interact_pointer_pos() {
+        let pointer = to_layer(pointer);
+        let node = node_at(&rects, pointer);
+
+        if response.double_clicked() {
+            match node {
+                Some(node) => {
+                    ui.memory_mut(|m| m.request_focus(id.with(("rename", &node))));
+                    renaming = Some((node.clone(), node));
+                }
+                None => edits.push(Edit::Add(pointer)),
+            }
+        } else if response.clicked() {
+            project.selection = match node {
+                Some(node) => Selection::Node(node),
+                None => edge_at(&scene, &rects, pointer, 8.0 / transform.scaling)
+                    .map(|(from, to)| Selection::Edge(from, to))
+                    .unwrap_or_default(),
+            };
+        } else if response.secondary_clicked() {
+            ui.data_mut(|d| d.insert_temp(menu_id, (node, pointer)));
+        }
+    }
+
+    let menu: Option<(Option<String>, Pos2)> = ui.data(|d| d.get_temp(menu_id));
+    response.context_menu(|ui| match menu.clone() {
+        Some((Some(node), _)) => {
+            if project.pinned.contains(&node) {
+                if ui.button("Unpin").clicked() {
+                    project.pinned.remove(&node);
+                    project.dirty = true;
+                    project.commit(false);
+                    ui.close_menu();
+                }
+            } else if ui.button("Pin").clicked() {
+                project.pinned.insert(node.clone());
+                project.dirty = true;
+                project.commit(false);
+                ui.close_menu();
+            }
+            if ui.button("Route from here").clicked() {
+                project.route.from = Some(node.clone());
+                project.route.update(&project.graph);
+                ui.close_menu();
+            }
+            if ui.button("Route to here").clicked() {
+                project.route.to = Some(node.clone());
+                project.route.update(&project.graph);
+                ui.close_menu();
+            }
+            if ui.button("Rename").clicked() {
+                ui.memory_mut(|m| m.request_focus(id.with(("rename", &node))));
+                renaming = Some((node.clone(), node.clone()));
+                ui.close_menu();
+            }
+            if ui.button("Delete").clicked() {
+                edits.push(Edit::Remove(Selection::Node(node)));
+                ui.close_menu();
+            }
         }
+        Some((None, pos)) => {
+            if ui.button("Add node").clicked() {
+                edits.push(Edit::Add(pos));
+                ui.close_menu();
+            }
+        }
+        None => {
+            ui.close_menu();
+        }
+    });
+
+    if !ui.ctx().wants_keyboard_input() && ui.input(|i| i.key_pressed(Key::Delete)) {
+        edits.push(Edit::Remove(project.selection.clone()));
+    }
+
+    //-----------------------------------------------------------------------//
+    // Painting
+
+    let painter = ui.painter_at(rect);
+    let canvas = Canvas {
+        painter: &painter,
+        transform,
+    };
+    let visible = transform.inverse() * rect;
+    let visuals = ui.visuals();
+    let replay = project
+        .player
+        .trace
+        .as_ref()
+        .map(|trace| trace.replay(project.player.step));
+    let trace_stroke = Stroke::new(2.5, Color32::from_rgb(230, 160, 40));
+    let route = project.route.path().unwrap_or_default();
+    let route_edges: HashSet<(&String, &String)> =
+        route.windows(2).map(|pair| (&pair[0], &pair[1])).collect();
+    // undirected edges are drawn once for both ways
+    let same = |a: &String, b: &String, from: &String, to: &String| {
+        (a == from && b == to) || (!scene.directed && a == to && b == from)
+    };
+    let route_stroke = Stroke::new(2.5, ROUTE_COLOR);
+    let searching = project.search.active();
+    let hits = project.search.hit_set();
+    let current_hit = project.search.current_hit();
+    let dimmed = |node: &String| searching && project.search.dim && !hits.contains(node);
+
+    for edge in &scene.edges {
+        let (from, to) = (&edge.from, &edge.to);
+        let (Some(from_rect), Some(to_rect)) = (rects.get(from), rects.get(to)) else {
+            continue;
+        };
+        if !edge_bounds(*from_rect, *to_rect).intersects(visible) {
+            continue;
+        }
+        let Some(curve) = edge_curve(&rects, edge) else {
+            continue;
+        };
+
+        let is_edge = |pair: &(String, String)| same(&pair.0, &pair.1, from, to);
+        let stroke = if matches!(&project.selection, Selection::Edge(a, b) if same(a, b, from, to))
+        {
+            Stroke::new(2.0, visuals.selection.stroke.color)
+        } else if replay
+            .as_ref()
+            .is_some_and(|replay| replay.edge.as_ref().is_some_and(is_edge))
+        {
+            trace_stroke
+        } else if route_edges.contains(&(from, to))
+            || (!scene.directed && route_edges.contains(&(to, from)))
+        {
+            route_stroke
+        } else if replay.as_ref().is_some_and(|replay| {
+            replay.parents.get(to) == Some(from)
+                || (!scene.directed && replay.parents.get(from) == Some(to))
+        }) {
+            Stroke::new(2.0, visuals.text_color())
+        } else {
+            Stroke::new(1.0, Color32::DARK_GRAY)
+        };
+        let stroke = if dimmed(from) && dimmed(to) {
+            Stroke::new(stroke.width, stroke.color.gamma_multiply(0.25))
+        } else {
+            stroke
+        };
+
+        paint_edge(
+            &canvas,
+            &curve,
+            scene.directed,
+            edge.weight,
+            stroke,
+            visuals.panel_fill,
+        );
     }
 
-    for (i, (node, pos)) in graph.iter().enumerate() {
+    for (node, node_rect) in &rects {
+        if !node_rect.intersects(visible) {
+            continue;
+        }
+
+        let state = replay
+            .as_ref()
+            .map_or(NodeState::Unseen, |replay| replay.state(node));
+        let current = replay
+            .as_ref()
+            .is_some_and(|replay| replay.current.as_ref() == Some(node));
+
+        let stroke = if matches!(&project.selection, Selection::Node(selected) if selected == node)
         {
-            let id = egui::Area::new(id.with(("bg", i)))
-                .fixed_pos(*pos)
-                .order(egui::Order::Background)
+            Stroke::new(2.0, visuals.selection.stroke.color)
+        } else if current {
+            trace_stroke
+        } else if current_hit == Some(node) {
+            Stroke::new(3.0, SEARCH_COLOR)
+        } else if route.contains(node) {
+            route_stroke
+        } else if searching && hits.contains(node) {
+            Stroke::new(2.0, SEARCH_COLOR)
+        } else if project.pinned.contains(node) {
+            Stroke::new(1.0, visuals.warn_fg_color)
+        } else if hovered.as_ref() == Some(node) {
+            visuals.widgets.hovered.bg_stroke
+        } else {
+            visuals.window_stroke
+        };
+
+        canvas.add(Shape::rect_filled(*node_rect, 40.0, visuals.panel_fill));
+        if let Some(fill) = state_color(state) {
+            canvas.add(Shape::rect_filled(*node_rect, 40.0, fill));
+        }
+        canvas.add(Shape::rect_stroke(*node_rect, 40.0, stroke));
+
+        if let Some(replay) = &replay {
+            if let Some(discovered) = replay.discovered.get(node) {
+                let times = match replay.finished.get(node) {
+                    Some(finished) => format!("{}/{}", discovered, finished),
+                    None => format!("{}/", discovered),
+                };
+                let galley = painter.layout_no_wrap(
+                    times,
+                    FontId::proportional(10.0),
+                    visuals.weak_text_color(),
+                );
+                let pos = node_rect.center_bottom() + Vec2::new(-galley.size().x / 2.0, 2.0);
+                canvas.add(Shape::galley(pos, galley, visuals.weak_text_color()));
+            }
+        }
+
+        let galley = painter.layout_no_wrap(node.clone(), font.clone(), visuals.text_color());
+        canvas.add(Shape::galley(
+            node_rect.min + Vec2::splat(NODE_MARGIN),
+            galley,
+            visuals.text_color(),
+        ));
+
+        if dimmed(node) {
+            canvas.add(Shape::rect_filled(
+                node_rect.expand(1.0),
+                40.0,
+                visuals.panel_fill.gamma_multiply(0.75),
+            ));
+        }
+    }
+
+    if let (Some(Drag::Connect(from)), Some(pointer)) = (&drag, pointer) {
+        if let Some(from_rect) = rects.get(from) {
+            let start = transform * from_rect.center();
+            painter.arrow(
+                start,
+                pointer - start,
+                Stroke::new(2.0, visuals.selection.stroke.color),
+            );
+        }
+    }
+
+    if project.minimap {
+        minimap_view(ui, project, &rects, rect, offset);
+    }
+
+    //-----------------------------------------------------------------------//
+    // Renaming
+
+    if let Some((node, text)) = &mut renaming {
+        if let Some(node_rect) = rects.get(node) {
+            let edit_id = id.with(("rename", &*node));
+            let mut finished = None;
+
+            egui::Area::new(id.with("rename_area"))
+                .fixed_pos(transform * node_rect.min)
+                .order(egui::Order::Foreground)
                 .show(ui.ctx(), |ui| {
-                    ui.set_clip_rect(transform.inverse() * rect);
-                    egui::Frame::default()
-                        .rounding(egui::Rounding::same(40.0))
-                        .inner_margin(egui::Margin::same(8.0))
-                        .stroke(ui.ctx().style().visuals.window_stroke)
-                        .fill(ui.style().visuals.panel_fill)
-                        .show(ui, |ui| {
-                            ui.label(node);
-                            let painter = ui.painter();
-
-                            for adj in adj.get_adj(&node) {
-                                painter.line_segment(
-                                    [
-                                        *graph.get(node).unwrap() + Vec2::new(20.0, 16.0),
-                                        *graph.get(&adj).unwrap() + Vec2::new(20.0, 16.0),
-                                    ],
-                                    Stroke::new(1.0, Color32::DARK_GRAY),
-                                );
-                            }
-                        });
-                })
-                .response
-                .layer_id;
-
-            ui.ctx().set_transform_layer(id, transform);
-        }
-        let id = egui::Area::new(id.with(("subarea", i)))
-            .fixed_pos(*pos)
-            .order(egui::Order::Foreground)
-            .show(ui.ctx(), |ui| {
-                ui.set_clip_rect(transform.inverse() * rect);
-                egui::Frame::default()
-                    .rounding(egui::Rounding::same(40.0))
-                    .inner_margin(egui::Margin::same(8.0))
-                    .stroke(ui.ctx().style().visuals.window_stroke)
-                    .fill(ui.style().visuals.panel_fill)
-                    .show(ui, |ui| {
-                        ui.style_mut().wrap = Some(false);
-
-                        ui.label(node);
-                    });
-            })
-            .response
-            .layer_id;
-
-        ui.ctx().set_transform_layer(id, transform);
+                    let edit = ui.add(
+                        egui::TextEdit::singleline(text)
+                            .id(edit_id)
+                            .desired_width(120.0),
+                    );
+                    if edit.lost_focus() {
+                        let cancelled = ui.input(|i| i.key_pressed(Key::Escape));
+                        finished = Some((!cancelled).then(|| text.clone()));
+                    }
+                });
+
+            if let Some(new) = finished {
+                if let Some(new) = new {
+                    edits.push(Edit::Rename(node.clone(), new));
+                }
+                renaming = None;
+            }
+        } else {
+            renaming = None;
+        }
+    }
+
+    ui.data_mut(|d| {
+        match renaming {
+            Some(renaming) => d.insert_temp(rename_id, renaming),
+            None => d.remove::<(String, String)>(rename_id),
+        }
+        match drag {
+            Some(drag) => d.insert_temp(drag_id, drag),
+            None => d.remove::<Drag>(drag_id),
+        }
+    });
+
+    //-----------------------------------------------------------------------//
+    // Out of sync
+
+    if !project.in_sync() {
+        // edits would be lost as soon as the text parses again
+        edits.clear();
+
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 0.0, visuals.panel_fill.gamma_multiply(0.7));
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "Fix the errors in the text to edit the graph",
+            font,
+            visuals.error_fg_color,
+        );
+    }
+
+    for edit in edits {
+        match edit {
+            Edit::Add(pos) => {
+                let node = project.add_node(pos);
+                project.selection = Selection::Node(node);
+            }
+            Edit::Connect(from, to) => {
+                if !project.graph.get_adj(&from).contains(&to) {
+                    project.set_edge(&from, &to, 1);
+                }
+                project.selection = Selection::Edge(from, to);
+            }
+            Edit::Rename(old, new) => {
+                // an invalid name just keeps the old one
+                let _ = project.rename_node(&old, &new);
+            }
+            Edit::Remove(selection) => {
+                project.selection = selection;
+                project.remove_selection();
+            }
+        }
     }
 }
 
@@ -0,0 +1,197 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use egui::Color32;
+
+use crate::{
+    gui::pages::{Project, Selection},
+    traversal::{Event, Kind, NodeState, Replay},
+};
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Fill for a node on the canvas while a trace is shown
+pub fn state_color(state: NodeState) -> Option<Color32> {
+    match state {
+        NodeState::Unseen => None,
+        NodeState::Frontier => Some(Color32::from_rgba_unmultiplied(70, 130, 230, 110)),
+        NodeState::Visited => Some(Color32::from_rgba_unmultiplied(230, 160, 40, 130)),
+        NodeState::Finished => Some(Color32::from_rgba_unmultiplied(70, 170, 90, 110)),
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Pick a traversal and a start node, then step through it on the canvas
+pub fn player_view(ui: &mut egui::Ui, project: &mut Project) {
+    let mut nodes: Vec<String> = project.graphic.keys().cloned().collect();
+    nodes.sort();
+
+    let player = &mut project.player;
+
+    ui.horizontal(|ui| {
+        for kind in [Kind::Bfs, Kind::Dfs] {
+            ui.selectable_value(&mut player.kind, kind, kind.name());
+        }
+    });
+
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_label("Start")
+            .selected_text(player.start.as_deref().unwrap_or("None"))
+            .show_ui(ui, |ui| {
+                for node in nodes {
+                    ui.selectable_value(&mut player.start, Some(node.clone()), node);
+                }
+            });
+
+        if let Selection::Node(node) = &project.selection {
+            if ui.button("Use selected").clicked() {
+                player.start = Some(node.clone());
+            }
+        }
+    });
+
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(player.start.is_some(), egui::Button::new("Run"))
+            .clicked()
+        {
+            player.error = player
+                .run(&project.graph, project.kind)
+                .err()
+                .map(|err| err.to_string());
+        }
+        if ui
+            .add_enabled(player.trace.is_some(), egui::Button::new("Clear"))
+            .clicked()
+        {
+            player.trace = None;
+            player.playing = false;
+        }
+    });
+
+    if let Some(error) = &player.error {
+        ui.colored_label(ui.visuals().error_fg_color, error);
+    }
+
+    let Some(trace) = &player.trace else {
+        return;
+    };
+    let steps = trace.steps.len();
+
+    ui.horizontal(|ui| {
+        let at_start = player.step <= 1;
+        let at_end = player.step >= steps;
+
+        if ui
+            .add_enabled(!at_start, egui::Button::new("First"))
+            .clicked()
+        {
+            player.step = 1;
+        }
+        if ui
+            .add_enabled(!at_start, egui::Button::new("Back"))
+            .clicked()
+        {
+            player.step -= 1;
+        }
+        let play = if player.playing { "Pause" } else { "Play" };
+        if ui.add_enabled(!at_end, egui::Button::new(play)).clicked() {
+            player.playing = !player.playing;
+        }
+        if ui.add_enabled(!at_end, egui::Button::new("Next")).clicked() {
+            player.step += 1;
+        }
+        if ui.add_enabled(!at_end, egui::Button::new("Last")).clicked() {
+            player.step = steps;
+        }
+    });
+
+    ui.add(egui::Slider::new(&mut player.step, 1..=steps.max(1)).text("Step"));
+    ui.add(
+        egui::Slider::new(&mut player.speed, 0.5..=20.0)
+            .logarithmic(true)
+            .text("Steps / s"),
+    );
+
+    let replay = trace.replay(player.step);
+
+    ui.separator();
+
+    ui.strong(format!("{} from {}", trace.kind.name(), trace.start));
+    if let Some(step) = trace.steps.get(player.step.saturating_sub(1)) {
+        ui.label(describe(&step.event));
+    }
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label(format!("{}:", trace.kind.frontier()));
+        if replay.frontier.is_empty() {
+            ui.weak("empty");
+        }
+        for node in &replay.frontier {
+            ui.monospace(node);
+        }
+    });
+
+    ui.horizontal_wrapped(|ui| {
+        for (state, name) in [
+            (NodeState::Frontier, "frontier"),
+            (NodeState::Visited, "visited"),
+            (NodeState::Finished, "finished"),
+        ] {
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+            if let Some(color) = state_color(state) {
+                ui.painter().rect_filled(rect, 2.0, color);
+            }
+            ui.label(name);
+        }
+    });
+
+    times_view(ui, &replay);
+}
+
+//---------------------------------------------------------------------------//
+
+fn describe(event: &Event) -> String {
+    match event {
+        Event::Discover { node, parent: None } => format!("Start at {}", node),
+        Event::Discover {
+            node,
+            parent: Some(parent),
+        } => format!("Discover {} from {}", node, parent),
+        Event::Dequeue(node) => format!("Dequeue {}", node),
+        Event::Examine { from, to, edge } => {
+            format!("Look at {} -> {}, a {} edge", from, to, edge.name())
+        }
+        Event::Finish(node) => format!("Finish {}", node),
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Discovery and finish times, in order of discovery
+fn times_view(ui: &mut egui::Ui, replay: &Replay) {
+    let mut nodes: Vec<(&String, &usize)> = replay.discovered.iter().collect();
+    nodes.sort_by_key(|(_, time)| **time);
+
+    egui::Grid::new("trace_times")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.strong("Node");
+            ui.strong("Discovered");
+            ui.strong("Finished");
+            ui.end_row();
+
+            for (node, discovered) in nodes {
+                ui.label(node);
+                ui.label(discovered.to_string());
+                match replay.finished.get(node) {
+                    Some(finished) => ui.label(finished.to_string()),
+                    None => ui.weak("-"),
+                };
+                ui.end_row();
+            }
+        });
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,94 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use egui::Color32;
+
+use crate::gui::pages::{Project, Selection};
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Outline of the nodes and edges on the route
+pub const ROUTE_COLOR: Color32 = Color32::from_rgb(220, 70, 160);
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Pick two nodes and see the shortest path between them on the canvas
+pub fn route_view(ui: &mut egui::Ui, project: &mut Project) {
+    let mut nodes: Vec<String> = project.graphic.keys().cloned().collect();
+    nodes.sort();
+
+    let route = &mut project.route;
+    let selected = match &project.selection {
+        Selection::Node(node) => Some(node),
+        _ => None,
+    };
+
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        changed |= ui
+            .selectable_value(&mut route.weighted, true, "Lowest cost")
+            .on_hover_text("Dijkstra's algorithm over the edge weights")
+            .changed();
+        changed |= ui
+            .selectable_value(&mut route.weighted, false, "Fewest edges")
+            .on_hover_text("Breadth-first search, ignoring weights")
+            .changed();
+    });
+
+    for (label, end) in [("From", &mut route.from), ("To", &mut route.to)] {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label(label)
+                .selected_text(end.as_deref().unwrap_or("None"))
+                .show_ui(ui, |ui| {
+                    for node in &nodes {
+                        changed |= ui.selectable_value(end, Some(node.clone()), node).changed();
+                    }
+                });
+
+            if let Some(node) = selected {
+                if ui.button("Use selected").clicked() {
+                    *end = Some(node.clone());
+                    changed = true;
+                }
+            }
+        });
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button("Swap").clicked() {
+            std::mem::swap(&mut route.from, &mut route.to);
+            changed = true;
+        }
+        if ui.button("Clear").clicked() {
+            route.from = None;
+            route.to = None;
+            changed = true;
+        }
+    });
+
+    if changed {
+        route.update(&project.graph);
+    }
+
+    match &route.result {
+        None => {
+            ui.weak("Pick both ends, here or from a node's right-click menu");
+        }
+        Some(Err(err)) => {
+            ui.colored_label(ui.visuals().error_fg_color, err);
+        }
+        Some(Ok(None)) => {
+            ui.label("No path");
+        }
+        Some(Ok(Some(path))) => {
+            ui.label(path.nodes.join(" → "));
+            ui.label(format!(
+                "Total cost {}, {} edges",
+                path.weight,
+                path.nodes.len() - 1
+            ));
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
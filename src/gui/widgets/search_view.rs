@@ -0,0 +1,99 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use cs_240_library::data_structures::graphs::IDefiniteGraph;
+use egui::{Color32, Key};
+
+use crate::gui::{
+    pages::{Project, Selection},
+    palette::SEARCH_SHORTCUT,
+    search::Mode,
+};
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Outline of the nodes matching the search
+pub const SEARCH_COLOR: Color32 = Color32::from_rgb(40, 170, 200);
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Query, match mode and the hits so far, Enter goes to the next hit
+pub fn search_bar(ui: &mut egui::Ui, project: &mut Project) {
+    let query_id = egui::Id::new("search_query");
+
+    if ui.input_mut(|i| i.consume_shortcut(&SEARCH_SHORTCUT)) {
+        ui.memory_mut(|m| m.request_focus(query_id));
+    }
+
+    let mut changed = false;
+    let mut step = None;
+
+    ui.horizontal(|ui| {
+        let search = &mut project.search;
+
+        let edit = ui.add(
+            egui::TextEdit::singleline(&mut search.query)
+                .id(query_id)
+                .hint_text("Find node")
+                .desired_width(160.0),
+        );
+        changed |= edit.changed();
+        if edit.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+            step = Some(ui.input(|i| i.modifiers.shift));
+            ui.memory_mut(|m| m.request_focus(query_id));
+        }
+
+        egui::ComboBox::from_id_source("search_mode")
+            .selected_text(search.mode.name())
+            .width(90.0)
+            .show_ui(ui, |ui| {
+                for mode in Mode::ALL {
+                    changed |= ui
+                        .selectable_value(&mut search.mode, mode, mode.name())
+                        .changed();
+                }
+            });
+
+        let has_hits = !search.hits.is_empty();
+        if ui
+            .add_enabled(has_hits, egui::Button::new("Previous"))
+            .on_hover_text("Shift+Enter")
+            .clicked()
+        {
+            step = Some(true);
+        }
+        if ui
+            .add_enabled(has_hits, egui::Button::new("Next"))
+            .on_hover_text("Enter")
+            .clicked()
+        {
+            step = Some(false);
+        }
+
+        ui.checkbox(&mut search.dim, "Dim others");
+
+        if let Some(error) = &search.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        } else if !search.query.is_empty() {
+            match search.current {
+                _ if !has_hits => ui.weak("No matches"),
+                Some(i) => ui.label(format!("{} of {}", i + 1, search.hits.len())),
+                None => ui.label(format!("{} matches", search.hits.len())),
+            };
+        }
+    });
+
+    if changed {
+        project.search.current = None;
+        // the graph, not the layout, which can lag behind it
+        project.search.update(project.graph.get_all().iter());
+    }
+
+    if let Some(back) = step {
+        if let Some(node) = project.search.step(back) {
+            project.selection = Selection::Node(node.clone());
+            project.focus = Some(node);
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,55 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use crate::gui::App;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// One tab per open project, with a dot for unsaved changes
+pub fn tab_bar(app: &mut App, ctx: &egui::Context) {
+    if app.tabs.is_empty() {
+        return;
+    }
+
+    let mut close = None;
+
+    egui::TopBottomPanel::top("tab_bar").show(ctx, |ui| {
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                for (i, project) in app.tabs.iter().enumerate() {
+                    let name = if project.dirty {
+                        format!("● {}", project.name())
+                    } else {
+                        project.name()
+                    };
+
+                    let mut tab = ui.selectable_label(i == app.current, name);
+                    if let Some(path) = &project.path {
+                        tab = tab.on_hover_text(path.to_string_lossy());
+                    }
+                    if tab.clicked() {
+                        app.current = i;
+                    }
+                    if tab.middle_clicked() {
+                        close = Some(i);
+                    }
+
+                    if ui.small_button("×").on_hover_text("Close").clicked() {
+                        close = Some(i);
+                    }
+
+                    ui.separator();
+                }
+
+                if ui.small_button("+").on_hover_text("New graph").clicked() {
+                    app.new_graph();
+                }
+            });
+        });
+    });
+
+    if let Some(i) = close {
+        app.close_tab(i);
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -1,6 +1,10 @@
 ///////////////////////////////////////////////////////////////////////////////
 
-use crate::gui::App;
+use crate::gui::{
+    modals::{Modal, PathInput},
+    palette::{PALETTE_SHORTCUT, REDO_SHORTCUT, UNDO_SHORTCUT},
+    App,
+};
 
 ///////////////////////////////////////////////////////////////////////////////
 
@@ -15,12 +19,14 @@ pub fn top_bar(app: &mut App, ctx: &egui::Context) {
                     ui.close_menu();
                 }
 
-                if ui.button("Open Graph").clicked() {
+                if ui.button("Open Graph ...").clicked() {
                     app.find_graph();
                     ui.close_menu();
                 }
 
                 ui.menu_button("Open Recent", |ui| {
+                    let mut clicked = None;
+
                     for path in &app.recent {
                         if ui
                             .button(
@@ -29,30 +35,65 @@ pub fn top_bar(app: &mut App, ctx: &egui::Context) {
                                     .to_str()
                                     .unwrap_or_default(),
                             )
+                            .on_hover_text(path.to_string_lossy())
                             .clicked()
                         {
-                            app.open_graph();
-                            ui.close_menu();
+                            clicked = Some(path.clone());
                             ui.close_menu();
                             break;
                         }
                     }
+
+                    if let Some(path) = clicked {
+                        if let Err(err) = app.open_graph(path.clone()) {
+                            app.modals.push(Modal::FindFile(PathInput {
+                                path: path.to_string_lossy().into_owned(),
+                                error: Some(err.to_string()),
+                            }));
+                        }
+                    }
                 });
 
                 ui.separator();
 
-                if ui.button("Save").clicked() {
+                let has_project = app.project().is_some();
+
+                if ui
+                    .add_enabled(has_project, egui::Button::new("Save"))
+                    .clicked()
+                {
                     ui.close_menu();
-                    todo!()
+                    if let Err(err) = app.save_graph() {
+                        app.modals.push(Modal::SaveFile(PathInput {
+                            path: Default::default(),
+                            error: Some(err.to_string()),
+                        }));
+                    }
+                }
+                if ui
+                    .add_enabled(has_project, egui::Button::new("Save As ..."))
+                    .clicked()
+                {
+                    ui.close_menu();
+                    app.save_graph_as();
                 }
-                if ui.button("Save As ...").clicked() {
+
+                ui.separator();
+
+                if ui
+                    .add_enabled(has_project, egui::Button::new("Export Image ..."))
+                    .clicked()
+                {
+                    app.export_image();
                     ui.close_menu();
-                    todo!()
                 }
 
                 ui.separator();
 
-                if ui.button("Close Graph").clicked() {
+                if ui
+                    .add_enabled(has_project, egui::Button::new("Close Graph"))
+                    .clicked()
+                {
                     app.close_graph();
                     ui.close_menu();
                 }
@@ -63,6 +104,47 @@ pub fn top_bar(app: &mut App, ctx: &egui::Context) {
                     ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                 }
             });
+
+            ui.menu_button("Edit", |ui| {
+                let (can_undo, can_redo) = app.project().map_or((false, false), |project| {
+                    (project.can_undo(), project.can_redo())
+                });
+
+                if ui
+                    .add_enabled(
+                        can_undo,
+                        egui::Button::new("Undo")
+                            .shortcut_text(ctx.format_shortcut(&UNDO_SHORTCUT)),
+                    )
+                    .clicked()
+                {
+                    if let Some(project) = app.project_mut() {
+                        project.undo();
+                    }
+                    ui.close_menu();
+                }
+                if ui
+                    .add_enabled(
+                        can_redo,
+                        egui::Button::new("Redo")
+                            .shortcut_text(ctx.format_shortcut(&REDO_SHORTCUT)),
+                    )
+                    .clicked()
+                {
+                    if let Some(project) = app.project_mut() {
+                        project.redo();
+                    }
+                    ui.close_menu();
+                }
+            });
+
+            if ui
+                .button("Commands")
+                .on_hover_text(ctx.format_shortcut(&PALETTE_SHORTCUT))
+                .clicked()
+            {
+                app.open_palette();
+            }
         });
     });
 }
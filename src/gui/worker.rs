@@ -0,0 +1,191 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use cs_240_library::data_structures::graphs::weighted_graph::WeightedGraph;
+
+use crate::layout::{self, smacof, Graphic, Monitor};
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// How often a running layout hands over its positions so far
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(50);
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// What a layout job does with the graph
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Task {
+    /// Keep the existing positions and only place new nodes
+    Update,
+    /// Lay out everything again, except pinned nodes
+    Relayout,
+    /// Stress majorization from the current positions
+    Refine,
+}
+
+//---------------------------------------------------------------------------//
+
+/// Result of a finished job
+pub struct Done {
+    pub graphic: Graphic,
+    pub stress: Option<f32>,
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Default)]
+struct State {
+    fraction: f32,
+    positions: Option<Graphic>,
+    done: Option<Done>,
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Default)]
+struct Shared {
+    cancelled: AtomicBool,
+    state: Mutex<State>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// A layout running on its own thread, cancelled when dropped
+pub struct Job {
+    shared: Arc<Shared>,
+    handle: JoinHandle<()>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Job {
+    //-------------------------------------------------------------------------//
+
+    /// Starts `task` from `graphic`, leaving the nodes in `pinned` alone
+    ///
+    /// The stress of the result is only measured if `measure_stress` is set,
+    /// since it needs all shortest paths
+    pub fn spawn(
+        task: Task,
+        graph: WeightedGraph<String, i32>,
+        graphic: Graphic,
+        pinned: Graphic,
+        params: layout::Params,
+        measure_stress: bool,
+    ) -> Self {
+        let shared = Arc::new(Shared::default());
+
+        let mut reporter = Reporter {
+            shared: shared.clone(),
+            last: Instant::now(),
+        };
+
+        let handle = thread::spawn(move || {
+            let graphic = match task {
+                Task::Update => layout::update(&graph, &graphic, &params, &mut reporter),
+                Task::Relayout => layout::compute(&graph, &pinned, &params, &mut reporter),
+                Task::Refine => {
+                    let mut graphic = graphic;
+                    smacof::simulate(&graph, &mut graphic, &pinned, &params.smacof, &mut reporter);
+                    graphic
+                }
+            };
+
+            if reporter.cancelled() {
+                return;
+            }
+
+            let stress =
+                measure_stress.then(|| smacof::stress(&graph, &graphic, params.smacof.edge_length));
+
+            let mut state = reporter.shared.state.lock().unwrap();
+            state.fraction = 1.0;
+            state.done = Some(Done { graphic, stress });
+        });
+
+        Self { shared, handle }
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// From 0 to 1
+    pub fn progress(&self) -> f32 {
+        self.shared.state.lock().unwrap().fraction
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// Positions so far, if new ones came in since the last call
+    pub fn take_positions(&self) -> Option<Graphic> {
+        self.shared.state.lock().unwrap().positions.take()
+    }
+
+    //-------------------------------------------------------------------------//
+
+    pub fn take_done(&self) -> Option<Done> {
+        self.shared.state.lock().unwrap().done.take()
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// Whether the thread has stopped, with or without a result
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    //-------------------------------------------------------------------------//
+}
+
+//---------------------------------------------------------------------------//
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        self.shared.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// The job's side of the shared state
+struct Reporter {
+    shared: Arc<Shared>,
+    last: Instant,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Reporter {
+    fn cancelled(&self) -> bool {
+        self.shared.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+impl Monitor for Reporter {
+    fn report(&mut self, fraction: f32, positions: Option<&dyn Fn() -> Graphic>) -> bool {
+        // building a snapshot is not free, so only every so often
+        let snapshot = positions
+            .filter(|_| self.last.elapsed() >= SNAPSHOT_INTERVAL)
+            .map(|positions| positions());
+
+        let mut state = self.shared.state.lock().unwrap();
+        state.fraction = fraction;
+        if let Some(snapshot) = snapshot {
+            state.positions = Some(snapshot);
+            self.last = Instant::now();
+        }
+
+        !self.cancelled()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -9,28 +9,201 @@ use anyhow::{Error, Ok, Result};
 use cs_240_library::{
     algorithms::graphs::{bfs::breadth_first_search, dfs::depth_first_search},
     data_structures::graphs::{
-        directed_graph::DirectedGraph, IDefiniteGraph, IGraph, IGraphEdgeMut, IGraphMut,
+        directed_graph::DirectedGraph, undirected_graph::UndirectedGraph,
+        weighted_graph::WeightedGraph, IDefiniteGraph, IGraph, IGraphEdgeMut, IGraphMut,
+        IWeightedGraph,
     },
 };
 
+use crate::{
+    document::{GraphFile, GraphKind},
+    graphs,
+    traversal::{EdgeKind, Event, Kind, Trace},
+};
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Everything the commands need from a graph
+///
+/// Implemented per graph kind, so undirected edges stay symmetric and
+/// weighted edges keep their weights
+pub trait ReplGraph:
+    GraphFile + Clone + IDefiniteGraph<String> + IGraph<String> + IGraphMut<String>
+{
+    /// `rest` holds any arguments after the two nodes
+    fn connect(&mut self, from: String, to: String, rest: &[String]) -> Result<()>;
+
+    fn disconnect(&mut self, from: String, to: String);
+
+    /// Adjacent nodes, formatted for `inspect`
+    fn describe_adj(&self, node: &String) -> Vec<String>;
+
+    /// Shortest path by number of edges, without `to` itself
+    fn route(&self, from: &String, to: &String) -> Option<Vec<String>>;
+
+    /// Topological order, and whether the graph had a cycle
+    fn schedule(&self) -> (Vec<String>, bool);
+}
+
+//---------------------------------------------------------------------------//
+
+impl ReplGraph for DirectedGraph<String> {
+    fn connect(&mut self, from: String, to: String, _: &[String]) -> Result<()> {
+        self.insert_edge(from, to);
+        Ok(())
+    }
+
+    fn disconnect(&mut self, from: String, to: String) {
+        self.remove_edge(from, to);
+    }
+
+    fn describe_adj(&self, node: &String) -> Vec<String> {
+        self.get_adj(node)
+    }
+
+    fn route(&self, from: &String, to: &String) -> Option<Vec<String>> {
+        let res = breadth_first_search(self.clone(), from.to_owned());
+        res.get(to).map(|path| path.iter().cloned().collect())
+    }
+
+    fn schedule(&self) -> (Vec<String>, bool) {
+        let (_, order, cyclic) = depth_first_search(self.clone());
+        (order.into_iter().collect(), cyclic)
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+impl ReplGraph for UndirectedGraph<String> {
+    fn connect(&mut self, from: String, to: String, _: &[String]) -> Result<()> {
+        self.insert_edge(from, to);
+        Ok(())
+    }
+
+    fn disconnect(&mut self, from: String, to: String) {
+        self.remove_edge(from, to);
+    }
+
+    fn describe_adj(&self, node: &String) -> Vec<String> {
+        self.get_adj(node)
+    }
+
+    fn route(&self, from: &String, to: &String) -> Option<Vec<String>> {
+        DirectedGraph::from(self.clone()).route(from, to)
+    }
+
+    fn schedule(&self) -> (Vec<String>, bool) {
+        DirectedGraph::from(self.clone()).schedule()
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+impl ReplGraph for WeightedGraph<String, i32> {
+    fn connect(&mut self, from: String, to: String, rest: &[String]) -> Result<()> {
+        let weight = match rest.first() {
+            Some(weight) => weight
+                .parse()
+                .map_err(|_| Error::msg("Invalid <weight> argument"))?,
+            None => 1,
+        };
+
+        graphs::set_edge(self, &from, &to, weight);
+        Ok(())
+    }
+
+    fn disconnect(&mut self, from: String, to: String) {
+        graphs::remove_edge(self, &from, &to);
+    }
+
+    fn describe_adj(&self, node: &String) -> Vec<String> {
+        self.get_adj_weighted(node)
+            .into_iter()
+            .map(|(adj, weight)| format!("{} ({})", adj, weight))
+            .collect()
+    }
+
+    fn route(&self, from: &String, to: &String) -> Option<Vec<String>> {
+        let res = breadth_first_search(self.clone(), from.to_owned());
+        res.get(to).map(|path| path.iter().cloned().collect())
+    }
+
+    fn schedule(&self) -> (Vec<String>, bool) {
+        let (_, order, cyclic) = depth_first_search(self.clone());
+        (order.into_iter().collect(), cyclic)
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 
-pub type StrGraph = DirectedGraph<String>;
+/// Where commands print to
+pub trait Output: Write {
+    /// Waits for the user between steps, false if they want to stop
+    fn pause(&mut self) -> Result<bool>;
+}
+
+//---------------------------------------------------------------------------//
+
+impl Output for io::Stdout {
+    fn pause(&mut self) -> Result<bool> {
+        write!(self, "      (enter to continue, q to stop) ")?;
+        self.flush()?;
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+
+        Ok(input.trim() != "q")
+    }
+}
 
 //---------------------------------------------------------------------------//
 
-type Callback = fn(&mut StrGraph, Vec<String>, &BTreeMap<String, Action>) -> Result<bool>;
+/// Collects the output, there is nobody to wait for
+impl Output for Vec<u8> {
+    fn pause(&mut self) -> Result<bool> {
+        Ok(true)
+    }
+}
 
 ///////////////////////////////////////////////////////////////////////////////
-struct Action {
+
+type Callback<G> =
+    fn(&mut G, Vec<String>, &BTreeMap<String, Action<G>>, &mut dyn Output) -> Result<bool>;
+
+///////////////////////////////////////////////////////////////////////////////
+struct Action<G> {
     desc: String,
-    callback: Callback,
+    callback: Callback<G>,
     pattern: String,
 }
 
 //---------------------------------------------------------------------------//
 
-fn action(desc: &str, cb: Callback, pt: &str) -> Action {
+/// A command as listed by `help`, for showing the commands elsewhere
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActionInfo {
+    pub name: String,
+    /// Arguments, `<required>` and `[optional]`
+    pub pattern: String,
+    pub desc: String,
+}
+
+//---------------------------------------------------------------------------//
+
+pub fn action_infos<G: ReplGraph>() -> Vec<ActionInfo> {
+    build_actions::<G>()
+        .into_iter()
+        .map(|(name, action)| ActionInfo {
+            name,
+            pattern: action.pattern,
+            desc: action.desc,
+        })
+        .collect()
+}
+
+//---------------------------------------------------------------------------//
+
+fn action<G>(desc: &str, cb: Callback<G>, pt: &str) -> Action<G> {
     Action {
         desc: desc.to_owned(),
         callback: cb,
@@ -40,20 +213,20 @@ fn action(desc: &str, cb: Callback, pt: &str) -> Action {
 
 //---------------------------------------------------------------------------//
 
-fn add_action(
-    actions: &mut BTreeMap<String, Action>,
+fn add_action<G>(
+    actions: &mut BTreeMap<String, Action<G>>,
     nm: &str,
     pt: &str,
     desc: &str,
-    cb: Callback,
+    cb: Callback<G>,
 ) {
     actions.insert(normalize(nm), action(desc, cb, pt));
 }
 
 ///////////////////////////////////////////////////////////////////////////////
 
-fn build_actions() -> BTreeMap<String, Action> {
-    let mut actions = BTreeMap::<String, Action>::new();
+fn build_actions<G: ReplGraph>() -> BTreeMap<String, Action<G>> {
+    let mut actions = BTreeMap::<String, Action<G>>::new();
 
     add_action(
         &mut actions,
@@ -79,7 +252,11 @@ fn build_actions() -> BTreeMap<String, Action> {
     add_action(
         &mut actions,
         "connect",
-        "<from> <to>",
+        t(
+            G::KIND == GraphKind::Weighted,
+            "<from> <to> [weight]",
+            "<from> <to>",
+        ),
         "Connects the two given nodes",
         connect,
     );
@@ -112,6 +289,13 @@ fn build_actions() -> BTreeMap<String, Action> {
         route,
     );
     add_action(&mut actions, "schedule", "", "Topo sort", schedule);
+    add_action(
+        &mut actions,
+        "trace",
+        "<bfs|dfs> <start> [pause]",
+        "Prints every step of a search, pausing for enter after each one with `pause`",
+        trace,
+    );
     add_action(&mut actions, "help", "", "Displays this message", help);
     add_action(&mut actions, "quit", "", "Quits the application", quit);
 
@@ -120,22 +304,25 @@ fn build_actions() -> BTreeMap<String, Action> {
 
 ///////////////////////////////////////////////////////////////////////////////
 
-pub fn interactive(graph: &mut StrGraph) -> Result<()> {
+pub fn interactive<G: ReplGraph>(graph: &mut G) -> Result<()> {
     let mut running = true;
     let mut input = String::new();
 
     let actions = build_actions();
+    let mut stdout = io::stdout();
 
     println!("Type `help` for usage");
 
     while running {
         print!("> ");
-        io::stdout().flush()?;
+        stdout.flush()?;
         match io::stdin().read_line(&mut input) {
-            std::result::Result::Ok(_) => match handle_input(&input, graph, &actions) {
-                std::result::Result::Ok(res) => running = res,
-                Err(err) => println!("{}", err),
-            },
+            std::result::Result::Ok(_) => {
+                match handle_input(&input, graph, &actions, &mut stdout) {
+                    std::result::Result::Ok(res) => running = res,
+                    Err(err) => println!("{}", err),
+                }
+            }
             Err(err) => println!("Error reading user input: {}", err),
         };
         input.clear();
@@ -147,21 +334,33 @@ pub fn interactive(graph: &mut StrGraph) -> Result<()> {
 
 //---------------------------------------------------------------------------//
 
-fn handle_input(
+/// Runs a single command, for when the input doesn't come from stdin
+///
+/// Returns false if the command was `quit`
+pub fn run_command<G: ReplGraph>(input: &str, graph: &mut G, out: &mut dyn Output) -> Result<bool> {
+    handle_input(input, graph, &build_actions(), out)
+}
+
+//---------------------------------------------------------------------------//
+
+fn handle_input<G: ReplGraph>(
     input: &str,
-    graph: &mut StrGraph,
-    actions: &BTreeMap<String, Action>,
+    graph: &mut G,
+    actions: &BTreeMap<String, Action<G>>,
+    out: &mut dyn Output,
 ) -> Result<bool> {
-    println!();
-    let input = normalize(input);
-    let args: Vec<String> = input.split_whitespace().map(|e| e.to_owned()).collect();
-    let name = args.get(0).ok_or(Error::msg("No command given"))?;
-
-    if let Some(action) = actions.get(name) {
-        (action.callback)(graph, args, &actions)
+    writeln!(out)?;
+    let mut args = split_args(input)?;
+    let name = args.first_mut().ok_or(Error::msg("No command given"))?;
+    *name = normalize(name);
+    let name = name.clone();
+    resolve_nodes(graph, &mut args[1..]);
+
+    if let Some(action) = actions.get(&name) {
+        (action.callback)(graph, args, &actions, out)
     } else {
-        println!("Unknown command, did you mean one of these?");
-        find_similar(name, &actions);
+        writeln!(out, "Unknown command, did you mean one of these?")?;
+        find_similar(&name, &actions, out)?;
         Ok(true)
     }
 }
@@ -174,7 +373,94 @@ fn normalize(input: &str) -> String {
 
 //---------------------------------------------------------------------------//
 
-fn find_similar(input: &str, actions: &BTreeMap<String, Action>) {
+/// Swaps each argument for the node it names, ignoring case
+///
+/// Names keep the case they were created with, so arguments that match more
+/// than one node, or none, are left as they are
+fn resolve_nodes<G: ReplGraph>(graph: &G, args: &mut [String]) {
+    let nodes = graph.get_all();
+
+    for arg in args.iter_mut().filter(|arg| !nodes.contains(arg)) {
+        let folded = arg.to_lowercase();
+        let mut matching = nodes.iter().filter(|node| node.to_lowercase() == folded);
+
+        if let (Some(node), None) = (matching.next(), matching.next()) {
+            *arg = node.clone();
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Splits a command line on whitespace, keeping anything in single or double
+/// quotes together so names can have spaces in them
+///
+/// Quotes only count at the start of an argument, so `it's` is left alone, and
+/// a backslash only escapes the next character inside them
+pub fn split_args(input: &str) -> Result<Vec<String>> {
+    let mut args = vec![];
+    let mut arg = String::new();
+    // an empty pair of quotes is still an argument
+    let mut started = false;
+    let mut quote = None;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', Some(_)) => {
+                let next = chars
+                    .next()
+                    .ok_or(Error::msg("Nothing to escape at the end of the line"))?;
+                arg.push(next);
+            }
+            (c, Some(open)) if c == open => quote = None,
+            (c, Some(_)) => arg.push(c),
+            ('"' | '\'', None) if !started => {
+                quote = Some(c);
+                started = true;
+            }
+            (c, None) if c.is_whitespace() => {
+                if started {
+                    args.push(std::mem::take(&mut arg));
+                    started = false;
+                }
+            }
+            (c, None) => {
+                arg.push(c);
+                started = true;
+            }
+        }
+    }
+
+    if let Some(open) = quote {
+        return Err(Error::msg(format!("Missing closing {}", open)));
+    }
+    if started {
+        args.push(arg);
+    }
+
+    Ok(args)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Quotes `arg` if it needs it to come back out of `split_args` as it is
+pub fn quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.starts_with(['"', '\'']) && !arg.contains(char::is_whitespace) {
+        return arg.to_owned();
+    }
+
+    let escaped = arg.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", escaped)
+}
+
+//---------------------------------------------------------------------------//
+
+fn find_similar<G>(
+    input: &str,
+    actions: &BTreeMap<String, Action<G>>,
+    out: &mut dyn Output,
+) -> Result<()> {
     let mut suggestions = BinaryHeap::with_capacity(actions.len());
 
     for other in actions.keys() {
@@ -185,18 +471,25 @@ fn find_similar(input: &str, actions: &BTreeMap<String, Action>) {
     }
 
     for suggestion in suggestions.into_sorted_vec().iter().take(3) {
-        println!("    {}", suggestion.word);
+        writeln!(out, "    {}", suggestion.word)?;
     }
+
+    Ok(())
 }
 
 ///////////////////////////////////////////////////////////////////////////////
 
-fn list(graph: &mut StrGraph, _: Vec<String>, _: &BTreeMap<String, Action>) -> Result<bool> {
+fn list<G: ReplGraph>(
+    graph: &mut G,
+    _: Vec<String>,
+    _: &BTreeMap<String, Action<G>>,
+    out: &mut dyn Output,
+) -> Result<bool> {
     let mut nodes = graph.get_all();
     nodes.sort();
 
     for node in nodes {
-        println!("- {}", node);
+        writeln!(out, "- {}", node)?;
     }
 
     Ok(true)
@@ -204,7 +497,12 @@ fn list(graph: &mut StrGraph, _: Vec<String>, _: &BTreeMap<String, Action>) -> R
 
 //---------------------------------------------------------------------------//
 
-fn add(graph: &mut StrGraph, args: Vec<String>, _: &BTreeMap<String, Action>) -> Result<bool> {
+fn add<G: ReplGraph>(
+    graph: &mut G,
+    args: Vec<String>,
+    _: &BTreeMap<String, Action<G>>,
+    _: &mut dyn Output,
+) -> Result<bool> {
     let node = args.get(1).ok_or(Error::msg("Missing <node> argument"))?;
 
     graph.insert_node(node.to_string());
@@ -214,7 +512,12 @@ fn add(graph: &mut StrGraph, args: Vec<String>, _: &BTreeMap<String, Action>) ->
 
 //---------------------------------------------------------------------------//
 
-fn remove(graph: &mut StrGraph, args: Vec<String>, _: &BTreeMap<String, Action>) -> Result<bool> {
+fn remove<G: ReplGraph>(
+    graph: &mut G,
+    args: Vec<String>,
+    _: &BTreeMap<String, Action<G>>,
+    _: &mut dyn Output,
+) -> Result<bool> {
     let node = args.get(1).ok_or(Error::msg("Missing <node> argument"))?;
 
     graph.remove_node(node.to_string());
@@ -224,38 +527,49 @@ fn remove(graph: &mut StrGraph, args: Vec<String>, _: &BTreeMap<String, Action>)
 
 //---------------------------------------------------------------------------//
 
-fn connect(graph: &mut StrGraph, args: Vec<String>, _: &BTreeMap<String, Action>) -> Result<bool> {
+fn connect<G: ReplGraph>(
+    graph: &mut G,
+    args: Vec<String>,
+    _: &BTreeMap<String, Action<G>>,
+    _: &mut dyn Output,
+) -> Result<bool> {
     let from = args.get(1).ok_or(Error::msg("Missing <from> argument"))?;
     let to = args.get(2).ok_or(Error::msg("Missing <to> argument"))?;
 
-    graph.insert_edge(from.to_string(), to.to_string());
+    graph.connect(from.to_string(), to.to_string(), &args[3..])?;
 
     Ok(true)
 }
 
 //---------------------------------------------------------------------------//
 
-fn disconnect(
-    graph: &mut StrGraph,
+fn disconnect<G: ReplGraph>(
+    graph: &mut G,
     args: Vec<String>,
-    _: &BTreeMap<String, Action>,
+    _: &BTreeMap<String, Action<G>>,
+    _: &mut dyn Output,
 ) -> Result<bool> {
     let from = args.get(1).ok_or(Error::msg("Missing <from> argument"))?;
     let to = args.get(2).ok_or(Error::msg("Missing <to> argument"))?;
 
-    graph.remove_edge(from.to_string(), to.to_string());
+    graph.disconnect(from.to_string(), to.to_string());
 
     Ok(true)
 }
 
 //---------------------------------------------------------------------------//
 
-fn filter(graph: &mut StrGraph, args: Vec<String>, _: &BTreeMap<String, Action>) -> Result<bool> {
+fn filter<G: ReplGraph>(
+    graph: &mut G,
+    args: Vec<String>,
+    _: &BTreeMap<String, Action<G>>,
+    out: &mut dyn Output,
+) -> Result<bool> {
     let filter = args.get(1).ok_or(Error::msg("Missing <filter> argument"))?;
 
     for node in graph.get_all() {
         if node.starts_with(filter) {
-            println!("- {}", node);
+            writeln!(out, "- {}", node)?;
         }
     }
 
@@ -264,12 +578,17 @@ fn filter(graph: &mut StrGraph, args: Vec<String>, _: &BTreeMap<String, Action>)
 
 //---------------------------------------------------------------------------//
 
-fn inspect(graph: &mut StrGraph, args: Vec<String>, _: &BTreeMap<String, Action>) -> Result<bool> {
+fn inspect<G: ReplGraph>(
+    graph: &mut G,
+    args: Vec<String>,
+    _: &BTreeMap<String, Action<G>>,
+    out: &mut dyn Output,
+) -> Result<bool> {
     let node = args.get(1).ok_or(Error::msg("Missing <node> argument"))?;
 
-    println!("- {}", node);
-    for adj in graph.get_adj(node) {
-        println!("    - {}", adj);
+    writeln!(out, "- {}", node)?;
+    for adj in graph.describe_adj(node) {
+        writeln!(out, "    - {}", adj)?;
     }
 
     Ok(true)
@@ -277,19 +596,22 @@ fn inspect(graph: &mut StrGraph, args: Vec<String>, _: &BTreeMap<String, Action>
 
 //---------------------------------------------------------------------------//
 
-fn route(graph: &mut StrGraph, args: Vec<String>, _: &BTreeMap<String, Action>) -> Result<bool> {
+fn route<G: ReplGraph>(
+    graph: &mut G,
+    args: Vec<String>,
+    _: &BTreeMap<String, Action<G>>,
+    out: &mut dyn Output,
+) -> Result<bool> {
     let from = args.get(1).ok_or(Error::msg("Missing <from> argument"))?;
     let to = args.get(2).ok_or(Error::msg("Missing <to> argument"))?;
 
-    let res = breadth_first_search(graph.clone(), from.to_owned());
-
-    if let Some(path) = res.get(to) {
+    if let Some(path) = graph.route(from, to) {
         for item in path {
-            println!("- {}", item);
+            writeln!(out, "- {}", item)?;
         }
-        println!("- {}", to);
+        writeln!(out, "- {}", to)?;
     } else {
-        println!("Couldn't reach {} from {}", to, from);
+        writeln!(out, "Couldn't reach {} from {}", to, from)?;
     }
 
     Ok(true)
@@ -297,26 +619,124 @@ fn route(graph: &mut StrGraph, args: Vec<String>, _: &BTreeMap<String, Action>)
 
 //---------------------------------------------------------------------------//
 
-fn schedule(graph: &mut StrGraph, _: Vec<String>, _: &BTreeMap<String, Action>) -> Result<bool> {
-    let (_, order, cyclic) = depth_first_search(graph.clone());
+fn schedule<G: ReplGraph>(
+    graph: &mut G,
+    _: Vec<String>,
+    _: &BTreeMap<String, Action<G>>,
+    out: &mut dyn Output,
+) -> Result<bool> {
+    let (order, cyclic) = graph.schedule();
 
     if cyclic {
         Err(Error::msg("Unable to sort cyclical graph"))
     } else {
         for node in order {
-            print!(" -> {}", node);
+            write!(out, " -> {}", node)?;
         }
-        println!();
+        writeln!(out)?;
         Ok(true)
     }
 }
 
 //---------------------------------------------------------------------------//
 
-fn help(_: &mut StrGraph, _: Vec<String>, actions: &BTreeMap<String, Action>) -> Result<bool> {
+fn trace<G: ReplGraph>(
+    graph: &mut G,
+    args: Vec<String>,
+    _: &BTreeMap<String, Action<G>>,
+    out: &mut dyn Output,
+) -> Result<bool> {
+    let kind = match args.get(1).map(|arg| arg.to_lowercase()).as_deref() {
+        Some("bfs") => Kind::Bfs,
+        Some("dfs") => Kind::Dfs,
+        Some(_) => return Err(Error::msg("Expected bfs or dfs")),
+        None => return Err(Error::msg("Missing <bfs|dfs> argument")),
+    };
+    let start = args.get(2).ok_or(Error::msg("Missing <start> argument"))?;
+    let pause = match args.get(3).map(|arg| arg.to_lowercase()).as_deref() {
+        Some("pause") => true,
+        Some(other) => return Err(Error::msg(format!("Unknown option {}", other))),
+        None => false,
+    };
+
+    let trace = Trace::new(&*graph, kind, start, G::KIND != GraphKind::Undirected)?;
+    let frontier = kind.frontier().to_lowercase();
+    let mut time = 0;
+
+    for (i, step) in trace.steps.iter().enumerate() {
+        let (stamp, text) = match &step.event {
+            Event::Discover { node, parent } => {
+                time += 1;
+                let text = match parent {
+                    Some(parent) => format!("discover {} from {}", node, parent),
+                    None => format!("start at {}", node),
+                };
+                (format!("{}", time), text)
+            }
+            Event::Dequeue(node) => (String::new(), format!("dequeue {}", node)),
+            Event::Examine { from, to, edge } => (
+                String::new(),
+                format!("  {} -> {} is a {} edge", from, to, edge.name()),
+            ),
+            Event::Finish(node) => {
+                time += 1;
+                let text = match kind {
+                    Kind::Bfs => format!("finish {}", node),
+                    Kind::Dfs => format!("pop {}, finished", node),
+                };
+                (format!("{}", time), text)
+            }
+        };
+
+        writeln!(
+            out,
+            "{:>4}  {:<36} {}: [{}]",
+            stamp,
+            text,
+            frontier,
+            step.frontier.join(", ")
+        )?;
+
+        if pause && i + 1 < trace.steps.len() && !out.pause()? {
+            return Ok(true);
+        }
+    }
+
+    let replay = trace.replay(trace.steps.len());
+
+    let mut nodes: Vec<(&String, &usize)> = replay.discovered.iter().collect();
+    nodes.sort_by_key(|(_, time)| **time);
+
+    writeln!(out)?;
+    writeln!(out, "discovered/finished")?;
+    for (node, discovered) in nodes {
+        writeln!(out, "- {} {}/{}", node, discovered, replay.finished[node])?;
+    }
+
+    writeln!(out)?;
+    for kind in EdgeKind::ALL {
+        let count = trace
+            .steps
+            .iter()
+            .filter(|step| matches!(step.event, Event::Examine { edge, .. } if edge == kind))
+            .count();
+        writeln!(out, "- {} edges: {}", kind.name(), count)?;
+    }
+
+    Ok(true)
+}
+
+//---------------------------------------------------------------------------//
+
+fn help<G: ReplGraph>(
+    _: &mut G,
+    _: Vec<String>,
+    actions: &BTreeMap<String, Action<G>>,
+    out: &mut dyn Output,
+) -> Result<bool> {
     for (name, action) in actions {
-        println!("- {} {}", name, action.pattern);
-        println!("    {}", action.desc);
+        writeln!(out, "- {} {}", name, action.pattern)?;
+        writeln!(out, "    {}", action.desc)?;
     }
 
     Ok(true)
@@ -324,7 +744,12 @@ fn help(_: &mut StrGraph, _: Vec<String>, actions: &BTreeMap<String, Action>) ->
 
 //---------------------------------------------------------------------------//
 
-fn quit(_: &mut StrGraph, _: Vec<String>, _: &BTreeMap<String, Action>) -> Result<bool> {
+fn quit<G: ReplGraph>(
+    _: &mut G,
+    _: Vec<String>,
+    _: &BTreeMap<String, Action<G>>,
+    _: &mut dyn Output,
+) -> Result<bool> {
     Ok(false)
 }
 
@@ -354,7 +779,7 @@ where
 ///////////////////////////////////////////////////////////////////////////////
 
 /// Edit distance between two words
-fn levenshtein_distance(first: &str, second: &str) -> usize {
+pub fn levenshtein_distance(first: &str, second: &str) -> usize {
     // iterative single row based on [30], [29], [27], and [31]
 
     // just changing the types to be easier to work with
@@ -416,3 +841,67 @@ impl Ord for RankedWord {
 }
 
 ///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn split_args_keeps_quoted_spaces_and_case() {
+        let args = split_args(r#"Connect "node 1" 'Node 2'  3"#).unwrap();
+        assert_eq!(args, ["Connect", "node 1", "Node 2", "3"]);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn split_args_escapes_only_in_quotes() {
+        let args = split_args(r#"add a\b it's "say \"hi\"" """#).unwrap();
+        assert_eq!(args, ["add", r"a\b", "it's", r#"say "hi""#, ""]);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn quote_round_trips() {
+        for arg in [
+            "plain",
+            "node 1",
+            r#"say "hi""#,
+            r"back\slash",
+            "it's",
+            "'quoted'",
+            "",
+        ] {
+            let line = format!("add {}", quote(arg));
+            assert_eq!(split_args(&line).unwrap(), ["add", arg]);
+        }
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn split_args_rejects_unclosed_quote() {
+        assert!(split_args(r#"add "node 1"#).is_err());
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn names_ignore_case_but_keep_it() {
+        let mut graph = DirectedGraph::<String>::new();
+        let mut out = vec![];
+
+        run_command(r#"ADD "Node A""#, &mut graph, &mut out).unwrap();
+        run_command("Add b", &mut graph, &mut out).unwrap();
+        run_command("add B", &mut graph, &mut out).unwrap();
+        run_command(r#"connect "node a" B"#, &mut graph, &mut out).unwrap();
+
+        assert_eq!(graph.get_all().len(), 2);
+        assert_eq!(graph.get_adj(&"Node A".to_owned()), ["b"]);
+    }
+
+    //-----------------------------------------------------------------------//
+}
@@ -0,0 +1,138 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use cs_240_library::data_structures::graphs::weighted_graph::WeightedGraph;
+use egui::{
+    ahash::{HashMap, HashSet},
+    Pos2,
+};
+
+use super::{bfs, centered, neighbours, Graphic};
+use crate::render;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Two columns, with every edge going from one to the other
+///
+/// Sides come from breadth-first depth parity, so a graph that isn't
+/// bipartite still gets split, just with some edges inside a column. Each
+/// column is then sorted by the average row of its neighbours to cut down on
+/// crossings
+pub fn layout(graph: &WeightedGraph<String, i32>, spacing: f32) -> Graphic {
+    let neighbours = neighbours(graph);
+
+    let mut nodes: Vec<&String> = neighbours.keys().collect();
+    nodes.sort();
+
+    let mut seen = HashSet::default();
+    let mut columns: [Vec<String>; 2] = Default::default();
+
+    for node in nodes {
+        if !seen.contains(node) {
+            for (node, _, depth) in bfs(&neighbours, node, &mut seen) {
+                columns[depth % 2].push(node);
+            }
+        }
+    }
+
+    for pass in 0..4 {
+        let (fixed, moving) = if pass % 2 == 0 { (0, 1) } else { (1, 0) };
+
+        let rows: HashMap<&String, usize> = columns[fixed]
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node, i))
+            .collect();
+
+        let mut keyed: Vec<(f32, String)> = columns[moving]
+            .iter()
+            .enumerate()
+            .map(|(i, node)| {
+                let adj: Vec<f32> = neighbours[node]
+                    .iter()
+                    .filter_map(|adj| rows.get(adj).map(|row| *row as f32))
+                    .collect();
+                let key = if adj.is_empty() {
+                    i as f32
+                } else {
+                    adj.iter().sum::<f32>() / adj.len() as f32
+                };
+                (key, node.clone())
+            })
+            .collect();
+        keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        columns[moving] = keyed.into_iter().map(|(_, node)| node).collect();
+    }
+
+    let left_width = columns[0]
+        .iter()
+        .map(|node| render::node_rect(node, Pos2::ZERO).width())
+        .fold(0.0, f32::max);
+    let xs = [300.0, 300.0 + left_width + 2.0 * spacing];
+    let row_gap = spacing / 2.0;
+
+    let mut graphic = Graphic::default();
+    for (column, x) in columns.into_iter().zip(xs) {
+        let height = column.len().saturating_sub(1) as f32 * row_gap;
+        for (i, node) in column.into_iter().enumerate() {
+            // both columns are centred on the same line
+            let center = Pos2::new(x, 300.0 + i as f32 * row_gap - height / 2.0);
+            let pos = centered(&node, center);
+            graphic.insert(node, pos);
+        }
+    }
+
+    graphic
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graphs, layout::assert_spread_out};
+
+    //-----------------------------------------------------------------------//
+
+    fn graph(edges: &[(&str, &str)]) -> WeightedGraph<String, i32> {
+        let mut nodes: Vec<String> = edges
+            .iter()
+            .flat_map(|(from, to)| [from.to_string(), to.to_string()])
+            .collect();
+        nodes.sort();
+        nodes.dedup();
+
+        let edges = edges
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string(), 1))
+            .collect();
+        graphs::build(nodes, edges)
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn parts_go_in_separate_columns() {
+        // students and the courses they take
+        let edges = [
+            ("ann", "art"),
+            ("ann", "math"),
+            ("bob", "math"),
+            ("cat", "art"),
+            ("cat", "bio"),
+        ];
+        let graph = graph(&edges);
+        let graphic = layout(&graph, 100.0);
+        assert_spread_out(&graph, &graphic);
+
+        let x = |node: &str| render::node_rect(node, graphic[node]).center().x;
+        let students = ["ann", "bob", "cat"].map(x);
+        let courses = ["art", "math", "bio"].map(x);
+
+        assert!(students.iter().all(|student| *student == students[0]));
+        assert!(courses.iter().all(|course| *course == courses[0]));
+        assert_ne!(students[0], courses[0]);
+    }
+
+    //-----------------------------------------------------------------------//
+}
@@ -0,0 +1,99 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use cs_240_library::data_structures::graphs::weighted_graph::WeightedGraph;
+use egui::{Pos2, Vec2};
+
+use super::{centered, components, Graphic};
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Places every node on one circle, `spacing` apart, starting at the top
+///
+/// Each connected component stays together in breadth-first order, with an
+/// empty slot between components
+pub fn layout(graph: &WeightedGraph<String, i32>, spacing: f32) -> Graphic {
+    let components = components(graph);
+
+    let mut slots: Vec<Option<String>> = vec![];
+    for component in components {
+        if !slots.is_empty() {
+            slots.push(None);
+        }
+        slots.extend(component.into_iter().map(Some));
+    }
+
+    let len = slots.len().max(1) as f32;
+    let radius = (len * spacing / TAU).max(spacing / 2.0);
+    let center = Pos2::new(300.0, 300.0) + Vec2::splat(radius);
+
+    slots
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, node)| {
+            let angle = TAU * i as f32 / len - FRAC_PI_2;
+            node.map(|node| {
+                let pos = centered(&node, center + Vec2::angled(angle) * radius);
+                (node, pos)
+            })
+        })
+        .collect()
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graphs, layout::assert_spread_out, render};
+
+    //-----------------------------------------------------------------------//
+
+    /// a -> b -> c and d -> e, apart from each other
+    fn graph() -> WeightedGraph<String, i32> {
+        graphs::build(
+            ["a", "b", "c", "d", "e"].map(String::from).to_vec(),
+            vec![
+                ("a".to_owned(), "b".to_owned(), 1),
+                ("b".to_owned(), "c".to_owned(), 1),
+                ("d".to_owned(), "e".to_owned(), 1),
+            ],
+        )
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn places_every_node_on_one_circle() {
+        let graph = graph();
+        let graphic = layout(&graph, 100.0);
+        assert_spread_out(&graph, &graphic);
+
+        // five nodes and the gap between the components
+        let radius = 6.0 * 100.0 / TAU;
+        let middle = Pos2::new(300.0, 300.0) + Vec2::splat(radius);
+
+        for (node, pos) in &graphic {
+            let dist = render::node_rect(node, *pos).center().distance(middle);
+            assert!((dist - radius).abs() < 0.01, "{} is {} out", node, dist);
+        }
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn components_stay_together() {
+        let graphic = layout(&graph(), 100.0);
+        let center = |node: &str| render::node_rect(node, graphic[node]).center();
+
+        // neighbouring slots are a little under one spacing apart
+        let dist = |a: &str, b: &str| center(a).distance(center(b));
+        assert!(dist("a", "b") < 100.0);
+        assert!(dist("b", "c") < 100.0);
+        assert!(dist("d", "e") < 100.0);
+        assert!(dist("c", "d") > 150.0);
+    }
+
+    //-----------------------------------------------------------------------//
+}
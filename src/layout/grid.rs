@@ -0,0 +1,78 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use cs_240_library::data_structures::graphs::weighted_graph::WeightedGraph;
+use egui::Pos2;
+
+use super::{centered, components, Graphic};
+use crate::render;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Fills a roughly square grid row by row
+///
+/// Nodes go in breadth-first order per connected component, so neighbours tend
+/// to end up close together. Columns are wide enough for the longest label
+pub fn layout(graph: &WeightedGraph<String, i32>, spacing: f32) -> Graphic {
+    let nodes: Vec<String> = components(graph).into_iter().flatten().collect();
+
+    let columns = (nodes.len() as f32).sqrt().ceil().max(1.0) as usize;
+    let widest = nodes
+        .iter()
+        .map(|node| render::node_rect(node, Pos2::ZERO).width())
+        .fold(0.0, f32::max);
+    let column_width = spacing.max(widest + spacing / 4.0);
+
+    nodes
+        .into_iter()
+        .enumerate()
+        .map(|(i, node)| {
+            let center = Pos2::new(
+                300.0 + (i % columns) as f32 * column_width,
+                300.0 + (i / columns) as f32 * spacing,
+            );
+            let pos = centered(&node, center);
+            (node, pos)
+        })
+        .collect()
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graphs, layout::assert_spread_out};
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn fills_a_square_row_by_row() {
+        let nodes: Vec<String> = (1..=7).map(|i| format!("node {}", i)).collect();
+        let graph = graphs::build(nodes, vec![]);
+        let graphic = layout(&graph, 100.0);
+        assert_spread_out(&graph, &graphic);
+
+        let center = |i: usize| {
+            let node = format!("node {}", i);
+            render::node_rect(&node, graphic[&node]).center()
+        };
+        // three columns for seven nodes
+        assert_eq!(center(1).y, center(3).y);
+        assert!(center(3).y < center(4).y);
+        assert_eq!(center(1).x, center(4).x);
+        assert_eq!(center(1).x, center(7).x);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn long_labels_widen_the_columns() {
+        let nodes = ["a", "a node with a very long name", "b"].map(String::from);
+        let graph = graphs::build(nodes.to_vec(), vec![]);
+        let graphic = layout(&graph, 40.0);
+
+        assert_spread_out(&graph, &graphic);
+    }
+
+    //-----------------------------------------------------------------------//
+}
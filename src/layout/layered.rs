@@ -0,0 +1,550 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use cs_240_library::data_structures::graphs::{
+    weighted_graph::WeightedGraph, IDefiniteGraph, IGraph,
+};
+use egui::{ahash::HashMap, Pos2, Vec2};
+
+use super::{Graphic, Monitor};
+use crate::render;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Which way edges point in a layered layout
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum Direction {
+    #[default]
+    TopDown,
+    LeftRight,
+}
+
+//---------------------------------------------------------------------------//
+
+/// Tuning for the layered layout
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Params {
+    pub direction: Direction,
+    /// Space between layers
+    pub layer_gap: f32,
+    /// Space between neighbouring nodes in a layer
+    pub node_gap: f32,
+    /// Barycenter sweeps used to reduce edge crossings
+    pub sweeps: u32,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            direction: Direction::TopDown,
+            layer_gap: 60.0,
+            node_gap: 40.0,
+            sweeps: 24,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Sugiyama style layered layout
+///
+/// Cycles are broken by reversing back edges, so this works on any graph but
+/// reads best on DAGs
+pub fn layout(
+    graph: &WeightedGraph<String, i32>,
+    params: &Params,
+    monitor: &mut dyn Monitor,
+) -> Graphic {
+    let mut nodes = graph.get_all();
+    nodes.sort();
+
+    let index: HashMap<&String, usize> = nodes.iter().enumerate().map(|(i, n)| (n, i)).collect();
+
+    let mut out: Vec<Vec<usize>> = vec![vec![]; nodes.len()];
+    for (i, node) in nodes.iter().enumerate() {
+        for adj in graph.get_adj(node) {
+            if let Some(&j) = index.get(&adj) {
+                if i != j && !out[i].contains(&j) {
+                    out[i].push(j);
+                }
+            }
+        }
+    }
+
+    let edges = acyclic_edges(&out);
+    let rank = layering(nodes.len(), &edges);
+
+    let mut layers = Layers::new(nodes.len(), &rank, &edges);
+    layers.order(params.sweeps, monitor);
+
+    let sizes: Vec<Vec2> = nodes
+        .iter()
+        .map(|node| render::node_rect(node, Pos2::ZERO).size())
+        .collect();
+
+    layers
+        .place(&sizes, params)
+        .into_iter()
+        .zip(nodes)
+        .map(|(center, node)| {
+            let size = render::node_rect(&node, Pos2::ZERO).size();
+            (node, center - size / 2.0)
+        })
+        .collect()
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// All edges, with the ones that close a cycle reversed
+fn acyclic_edges(out: &[Vec<usize>]) -> Vec<(usize, usize)> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        Open,
+        Done,
+    }
+
+    let mut state = vec![State::New; out.len()];
+    let mut edges = vec![];
+
+    for start in 0..out.len() {
+        if state[start] != State::New {
+            continue;
+        }
+
+        state[start] = State::Open;
+        let mut stack = vec![(start, 0)];
+
+        while let Some((node, next)) = stack.pop() {
+            let Some(&adj) = out[node].get(next) else {
+                state[node] = State::Done;
+                continue;
+            };
+            stack.push((node, next + 1));
+
+            match state[adj] {
+                State::Open => edges.push((adj, node)),
+                State::Done => edges.push((node, adj)),
+                State::New => {
+                    edges.push((node, adj));
+                    state[adj] = State::Open;
+                    stack.push((adj, 0));
+                }
+            }
+        }
+    }
+
+    edges.sort();
+    edges.dedup();
+    edges
+}
+
+//---------------------------------------------------------------------------//
+
+/// Longest path layering, with sources moved down next to their children
+fn layering(len: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut incoming = vec![0; len];
+    let mut out: Vec<Vec<usize>> = vec![vec![]; len];
+    for &(from, to) in edges {
+        incoming[to] += 1;
+        out[from].push(to);
+    }
+
+    let mut order: Vec<usize> = (0..len).filter(|i| incoming[*i] == 0).collect();
+    let mut remaining = incoming.clone();
+    let mut next = 0;
+    while next < order.len() {
+        for &adj in &out[order[next]] {
+            remaining[adj] -= 1;
+            if remaining[adj] == 0 {
+                order.push(adj);
+            }
+        }
+        next += 1;
+    }
+
+    let mut rank = vec![0; len];
+    for &node in &order {
+        for &adj in &out[node] {
+            rank[adj] = rank[adj].max(rank[node] + 1);
+        }
+    }
+
+    for &node in order.iter().rev() {
+        if incoming[node] == 0 {
+            if let Some(lowest) = out[node].iter().map(|adj| rank[*adj]).min() {
+                rank[node] = lowest - 1;
+            }
+        }
+    }
+
+    rank
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Nodes split into layers, with dummy nodes on edges that span several
+///
+/// Ids below `real` are graph nodes, the rest are dummies
+struct Layers {
+    real: usize,
+    layers: Vec<Vec<usize>>,
+    up: Vec<Vec<usize>>,
+    down: Vec<Vec<usize>>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Layers {
+    fn new(real: usize, rank: &[usize], edges: &[(usize, usize)]) -> Self {
+        let depth = rank.iter().max().map_or(0, |max| max + 1);
+
+        let mut res = Self {
+            real,
+            layers: vec![vec![]; depth],
+            up: vec![vec![]; real],
+            down: vec![vec![]; real],
+        };
+
+        for (node, &layer) in rank.iter().enumerate() {
+            res.layers[layer].push(node);
+        }
+
+        for &(from, to) in edges {
+            let mut prev = from;
+            for layer in rank[from] + 1..rank[to] {
+                let dummy = res.up.len();
+                res.up.push(vec![]);
+                res.down.push(vec![]);
+                res.layers[layer].push(dummy);
+                res.link(prev, dummy);
+                prev = dummy;
+            }
+            res.link(prev, to);
+        }
+
+        res
+    }
+
+    //-----------------------------------------------------------------------//
+
+    fn link(&mut self, from: usize, to: usize) {
+        self.down[from].push(to);
+        self.up[to].push(from);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    /// Reorders each layer by the average position of its neighbours,
+    /// sweeping down and up, and keeps the order with the fewest crossings
+    fn order(&mut self, sweeps: u32, monitor: &mut dyn Monitor) {
+        let mut best = self.layers.clone();
+        let mut best_crossings = self.crossings();
+
+        for sweep in 0..sweeps {
+            let downwards = sweep % 2 == 0;
+
+            let layers: Vec<usize> = if downwards {
+                (1..self.layers.len()).collect()
+            } else {
+                (0..self.layers.len().saturating_sub(1)).rev().collect()
+            };
+
+            for layer in layers {
+                let (neighbours, fixed) = if downwards {
+                    (&self.up, layer - 1)
+                } else {
+                    (&self.down, layer + 1)
+                };
+                let pos = self.positions(fixed);
+
+                let mut keyed: Vec<(f32, usize)> = self.layers[layer]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &node)| {
+                        let adj = &neighbours[node];
+                        let key = if adj.is_empty() {
+                            i as f32
+                        } else {
+                            adj.iter().map(|n| pos[n] as f32).sum::<f32>() / adj.len() as f32
+                        };
+                        (key, node)
+                    })
+                    .collect();
+                keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+                self.layers[layer] = keyed.into_iter().map(|(_, node)| node).collect();
+            }
+
+            let crossings = self.crossings();
+            if crossings < best_crossings {
+                best = self.layers.clone();
+                best_crossings = crossings;
+            }
+
+            if !monitor.report((sweep + 1) as f32 / sweeps as f32, None) {
+                break;
+            }
+        }
+
+        self.layers = best;
+    }
+
+    //-----------------------------------------------------------------------//
+
+    /// Index of each node within `layer`
+    fn positions(&self, layer: usize) -> HashMap<usize, usize> {
+        self.layers[layer]
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (*node, i))
+            .collect()
+    }
+
+    //-----------------------------------------------------------------------//
+
+    fn crossings(&self) -> usize {
+        let mut total = 0;
+
+        for layer in 1..self.layers.len() {
+            let above = self.positions(layer - 1);
+            let below = self.positions(layer);
+
+            let mut edges: Vec<(usize, usize)> = self.layers[layer]
+                .iter()
+                .flat_map(|node| self.up[*node].iter().map(|adj| (above[adj], below[node])))
+                .collect();
+            edges.sort();
+
+            for (i, a) in edges.iter().enumerate() {
+                total += edges[i + 1..]
+                    .iter()
+                    .filter(|b| b.0 > a.0 && b.1 < a.1)
+                    .count();
+            }
+        }
+
+        total
+    }
+
+    //-----------------------------------------------------------------------//
+
+    /// Centre of every real node
+    ///
+    /// Nodes are pulled towards their neighbours in the layers above and
+    /// below, while keeping their order and spacing within the layer
+    fn place(&self, sizes: &[Vec2], params: &Params) -> Vec<Pos2> {
+        let (across, along): (fn(Vec2) -> f32, fn(Vec2) -> f32) = match params.direction {
+            Direction::TopDown => (|v: Vec2| v.x, |v: Vec2| v.y),
+            Direction::LeftRight => (|v: Vec2| v.y, |v: Vec2| v.x),
+        };
+        let width = |node: usize| {
+            if node < self.real {
+                across(sizes[node])
+            } else {
+                0.0
+            }
+        };
+
+        let mut coord = vec![0.0; self.up.len()];
+        for layer in &self.layers {
+            let desired = vec![0.0; layer.len()];
+            for (node, x) in layer
+                .iter()
+                .zip(pack(layer, &desired, &width, params.node_gap))
+            {
+                coord[*node] = x;
+            }
+        }
+
+        for pass in 0..8 {
+            let neighbours = if pass % 2 == 0 { &self.up } else { &self.down };
+
+            for layer in &self.layers {
+                let desired: Vec<f32> = layer
+                    .iter()
+                    .map(|node| {
+                        let adj = &neighbours[*node];
+                        if adj.is_empty() {
+                            coord[*node]
+                        } else {
+                            adj.iter().map(|n| coord[*n]).sum::<f32>() / adj.len() as f32
+                        }
+                    })
+                    .collect();
+
+                for (node, x) in layer
+                    .iter()
+                    .zip(pack(layer, &desired, &width, params.node_gap))
+                {
+                    coord[*node] = x;
+                }
+            }
+        }
+
+        let mut depth = vec![0.0; self.layers.len()];
+        let mut offset = 0.0;
+        for (layer, nodes) in self.layers.iter().enumerate() {
+            let extent = nodes
+                .iter()
+                .filter(|node| **node < self.real)
+                .map(|node| along(sizes[*node]))
+                .fold(0.0, f32::max);
+
+            depth[layer] = offset + extent / 2.0;
+            offset += extent + params.layer_gap;
+        }
+
+        let mut centers = vec![Pos2::ZERO; self.real];
+        for (layer, nodes) in self.layers.iter().enumerate() {
+            for &node in nodes.iter().filter(|node| **node < self.real) {
+                centers[node] = match params.direction {
+                    Direction::TopDown => Pos2::new(coord[node], depth[layer]),
+                    Direction::LeftRight => Pos2::new(depth[layer], coord[node]),
+                };
+            }
+        }
+
+        centers
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Coordinates as close to `desired` as possible while keeping the nodes in
+/// order and `gap` apart
+///
+/// Least squares with ordering constraints, solved by pooling adjacent
+/// violators
+fn pack(layer: &[usize], desired: &[f32], width: &impl Fn(usize) -> f32, gap: f32) -> Vec<f32> {
+    let mut offsets = Vec::with_capacity(layer.len());
+    let mut offset = 0.0;
+    for (i, &node) in layer.iter().enumerate() {
+        if i > 0 {
+            offset += (width(layer[i - 1]) + width(node)) / 2.0 + gap;
+        }
+        offsets.push(offset);
+    }
+
+    // blocks of (mean, count) with non-decreasing means
+    let mut blocks: Vec<(f32, usize)> = vec![];
+    for (d, o) in desired.iter().zip(&offsets) {
+        let mut block = (d - o, 1);
+        while let Some(&(mean, count)) = blocks.last() {
+            if mean <= block.0 {
+                break;
+            }
+            blocks.pop();
+            let total = count + block.1;
+            block = (
+                (mean * count as f32 + block.0 * block.1 as f32) / total as f32,
+                total,
+            );
+        }
+        blocks.push(block);
+    }
+
+    blocks
+        .into_iter()
+        .flat_map(|(mean, count)| std::iter::repeat(mean).take(count))
+        .zip(offsets)
+        .map(|(z, o)| z + o)
+        .collect()
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs;
+
+    //-----------------------------------------------------------------------//
+
+    fn graph(edges: &[(&str, &str)]) -> WeightedGraph<String, i32> {
+        let mut nodes: Vec<String> = edges
+            .iter()
+            .flat_map(|(from, to)| [from.to_string(), to.to_string()])
+            .collect();
+        nodes.sort();
+        nodes.dedup();
+
+        let edges = edges
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string(), 1))
+            .collect();
+        graphs::build(nodes, edges)
+    }
+
+    //-----------------------------------------------------------------------//
+
+    fn center(graphic: &Graphic, node: &str) -> Pos2 {
+        render::node_rect(node, graphic[node]).center()
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn diamond_goes_down_in_layers() {
+        let graph = graph(&[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d")]);
+        let graphic = layout(&graph, &Params::default(), &mut ());
+        let y = |node| center(&graphic, node).y;
+
+        assert!(y("a") < y("b"));
+        assert_eq!(y("b"), y("c"));
+        assert!(y("c") < y("d"));
+
+        let (b, c) = (
+            render::node_rect("b", graphic["b"]),
+            render::node_rect("c", graphic["c"]),
+        );
+        assert!(!b.intersects(c));
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn left_right_goes_across() {
+        let graph = graph(&[("a", "b"), ("b", "c")]);
+        let params = Params {
+            direction: Direction::LeftRight,
+            ..Default::default()
+        };
+        let graphic = layout(&graph, &params, &mut ());
+        let x = |node| center(&graphic, node).x;
+
+        assert!(x("a") < x("b"));
+        assert!(x("b") < x("c"));
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn cycles_are_broken() {
+        // 0 -> 1 -> 2 -> 0
+        let edges = acyclic_edges(&[vec![1], vec![2], vec![0]]);
+        let rank = layering(3, &edges);
+
+        assert_eq!(edges.len(), 3);
+        for (from, to) in edges {
+            assert!(rank[from] < rank[to]);
+        }
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn ordering_removes_avoidable_crossings() {
+        // 0 -> 3 and 1 -> 2 cross in the initial order
+        let edges = [(0, 3), (1, 2)];
+        let mut layers = Layers::new(4, &[0, 0, 1, 1], &edges);
+        assert_eq!(layers.crossings(), 1);
+
+        layers.order(4, &mut ());
+        assert_eq!(layers.crossings(), 0);
+    }
+
+    //-----------------------------------------------------------------------//
+}
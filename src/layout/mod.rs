@@ -0,0 +1,441 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use cs_240_library::{
+    algorithms::graphs::dfs::depth_first_search,
+    data_structures::graphs::{weighted_graph::WeightedGraph, IDefiniteGraph, IGraph},
+};
+use egui::{
+    ahash::{HashMap, HashMapExt, HashSet},
+    Pos2, Vec2,
+};
+
+use crate::render;
+
+///////////////////////////////////////////////////////////////////////////////
+
+pub mod bipartite;
+pub mod circular;
+pub mod grid;
+pub mod layered;
+pub mod quadtree;
+pub mod radial;
+pub mod smacof;
+pub mod spring;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Node name to top-left position of the node
+pub type Graphic = HashMap<String, Pos2>;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Hears how far along a long running layout is, and can stop it early
+pub trait Monitor {
+    /// `fraction` goes from 0 to 1, `positions` builds the layout so far when
+    /// there is one to look at
+    ///
+    /// Returning false cancels the layout, which then returns what it has
+    fn report(&mut self, fraction: f32, positions: Option<&dyn Fn() -> Graphic>) -> bool;
+}
+
+//---------------------------------------------------------------------------//
+
+/// Nobody is listening
+impl Monitor for () {
+    fn report(&mut self, _: f32, _: Option<&dyn Fn() -> Graphic>) -> bool {
+        true
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Top-left position that puts the node's centre at `center`
+fn centered(node: &str, center: Pos2) -> Pos2 {
+    center - render::node_rect(node, Pos2::ZERO).size() / 2.0
+}
+
+//---------------------------------------------------------------------------//
+
+/// Neighbours of every node, ignoring edge direction, sorted
+fn neighbours(graph: &WeightedGraph<String, i32>) -> HashMap<String, Vec<String>> {
+    let mut res: HashMap<String, Vec<String>> = HashMap::new();
+
+    for node in graph.get_all() {
+        res.entry(node.clone()).or_default();
+        for adj in graph.get_adj(&node) {
+            if adj != node {
+                res.entry(node.clone()).or_default().push(adj.clone());
+                res.entry(adj).or_default().push(node.clone());
+            }
+        }
+    }
+
+    for adj in res.values_mut() {
+        adj.sort();
+        adj.dedup();
+    }
+
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// Breadth-first order from `start`, each node with its parent and depth
+fn bfs(
+    neighbours: &HashMap<String, Vec<String>>,
+    start: &str,
+    seen: &mut HashSet<String>,
+) -> Vec<(String, Option<String>, usize)> {
+    let mut order = vec![(start.to_owned(), None, 0)];
+    seen.insert(start.to_owned());
+
+    let mut next = 0;
+    while next < order.len() {
+        let (node, _, depth) = order[next].clone();
+        for adj in neighbours.get(&node).into_iter().flatten() {
+            if seen.insert(adj.clone()) {
+                order.push((adj.clone(), Some(node.clone()), depth + 1));
+            }
+        }
+        next += 1;
+    }
+
+    order
+}
+
+//---------------------------------------------------------------------------//
+
+/// Connected components ignoring edge direction, each in breadth-first order
+/// from its alphabetically first node
+fn components(graph: &WeightedGraph<String, i32>) -> Vec<Vec<String>> {
+    let neighbours = neighbours(graph);
+
+    let mut nodes: Vec<&String> = neighbours.keys().collect();
+    nodes.sort();
+
+    let mut seen = HashSet::default();
+    let mut res = vec![];
+
+    for node in nodes {
+        if !seen.contains(node) {
+            res.push(
+                bfs(&neighbours, node, &mut seen)
+                    .into_iter()
+                    .map(|(node, _, _)| node)
+                    .collect(),
+            );
+        }
+    }
+
+    res
+}
+
+//---------------------------------------------------------------------------//
+
+/// Starting positions on a sunflower spiral, evenly spread however many nodes
+/// there are
+///
+/// Nodes in `pinned` start at their pinned position
+fn spiral(graph: &WeightedGraph<String, i32>, pinned: &Graphic, spacing: f32) -> Graphic {
+    let mut graphic = Graphic::default();
+
+    for (i, node) in graph.get_all().into_iter().enumerate() {
+        let angle = i as f32 * std::f32::consts::PI * (3.0 - 5f32.sqrt());
+        let radius = spacing * (i as f32).sqrt();
+
+        let pos = pinned
+            .get(&node)
+            .copied()
+            .unwrap_or(Pos2::new(300.0, 300.0) + Vec2::angled(angle) * radius);
+        graphic.insert(node, pos);
+    }
+
+    graphic
+}
+
+//---------------------------------------------------------------------------//
+
+/// Fails unless every node has a position and no two node boxes overlap
+#[cfg(test)]
+fn assert_spread_out(graph: &WeightedGraph<String, i32>, graphic: &Graphic) {
+    let mut nodes = graph.get_all();
+    nodes.sort();
+
+    let rects: Vec<_> = nodes
+        .iter()
+        .map(|node| {
+            let pos = graphic
+                .get(node)
+                .unwrap_or_else(|| panic!("{} wasn't placed", node));
+            (node, render::node_rect(node, *pos))
+        })
+        .collect();
+
+    for (i, (a, rect)) in rects.iter().enumerate() {
+        for (b, other) in &rects[i + 1..] {
+            assert!(!rect.intersects(*other), "{} overlaps {}", a, b);
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Layout algorithms `compute` can use
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    clap::ValueEnum,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+pub enum Method {
+    /// Layered layout for DAGs, spring layout otherwise
+    #[default]
+    Auto,
+    /// Layers top to bottom following edge direction
+    Layered,
+    /// Force-directed
+    Spring,
+    /// Distances match shortest paths
+    Stress,
+    /// One circle, grouped by connected component
+    Circular,
+    /// Rings around a root node
+    Radial,
+    Grid,
+    /// Two columns
+    Bipartite,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Method {
+    pub const ALL: [Method; 8] = [
+        Method::Auto,
+        Method::Layered,
+        Method::Spring,
+        Method::Stress,
+        Method::Circular,
+        Method::Radial,
+        Method::Grid,
+        Method::Bipartite,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Method::Auto => "Automatic",
+            Method::Layered => "Layered",
+            Method::Spring => "Spring",
+            Method::Stress => "Stress majorization",
+            Method::Circular => "Circular",
+            Method::Radial => "Radial tree",
+            Method::Grid => "Grid",
+            Method::Bipartite => "Bipartite",
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Params {
+    pub method: Method,
+    pub layered: layered::Params,
+    pub spring: spring::Params,
+    pub smacof: smacof::Params,
+
+    /// Distance between neighbouring nodes in the circular, radial, grid and
+    /// bipartite layouts
+    pub spacing: f32,
+
+    /// Centre of the radial layout, picked automatically if unset or missing
+    pub root: Option<String>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            method: Default::default(),
+            layered: Default::default(),
+            spring: Default::default(),
+            smacof: Default::default(),
+            spacing: 120.0,
+            root: None,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Lays out the graph, leaving the nodes in `pinned` where they are
+pub fn compute(
+    graph: &WeightedGraph<String, i32>,
+    pinned: &Graphic,
+    params: &Params,
+    monitor: &mut dyn Monitor,
+) -> Graphic {
+    match params.method {
+        Method::Auto => {
+            let (_, _, cyclic) = depth_first_search(graph.clone());
+
+            if cyclic {
+                spring::layout(graph, pinned, &params.spring, monitor)
+            } else {
+                with_pinned(layered::layout(graph, &params.layered, monitor), pinned)
+            }
+        }
+        Method::Layered => with_pinned(layered::layout(graph, &params.layered, monitor), pinned),
+        Method::Spring => spring::layout(graph, pinned, &params.spring, monitor),
+        Method::Stress => smacof::layout(graph, pinned, &params.smacof, monitor),
+        Method::Circular => with_pinned(circular::layout(graph, params.spacing), pinned),
+        Method::Radial => with_pinned(
+            radial::layout(graph, params.root.as_deref(), params.spacing),
+            pinned,
+        ),
+        Method::Grid => with_pinned(grid::layout(graph, params.spacing), pinned),
+        Method::Bipartite => with_pinned(bipartite::layout(graph, params.spacing), pinned),
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// Moves pinned nodes back to their pins after a layout that ignores them
+fn with_pinned(mut graphic: Graphic, pinned: &Graphic) -> Graphic {
+    for (node, pos) in pinned {
+        if let Some(old) = graphic.get_mut(node) {
+            *old = *pos;
+        }
+    }
+    graphic
+}
+
+//---------------------------------------------------------------------------//
+
+/// Keeps the positions in `previous` and only places nodes that are new
+///
+/// Falls back to `compute` when none of the nodes have a position yet
+pub fn update(
+    graph: &WeightedGraph<String, i32>,
+    previous: &Graphic,
+    params: &Params,
+    monitor: &mut dyn Monitor,
+) -> Graphic {
+    let mut nodes = graph.get_all();
+    nodes.sort();
+
+    let mut graphic: Graphic = nodes
+        .iter()
+        .filter_map(|node| previous.get(node).map(|pos| (node.clone(), *pos)))
+        .collect();
+
+    if graphic.is_empty() {
+        return compute(graph, &Graphic::default(), params, monitor);
+    }
+
+    let mut incoming: HashMap<String, Vec<String>> = HashMap::new();
+    for node in &nodes {
+        for adj in graph.get_adj(node) {
+            incoming.entry(adj).or_default().push(node.clone());
+        }
+    }
+
+    let right = graphic.values().map(|pos| pos.x).fold(f32::MIN, f32::max) + 150.0;
+    let mut top = graphic.values().map(|pos| pos.y).fold(f32::MAX, f32::min);
+    let mut occupied = Occupancy::new(graphic.values());
+
+    for node in nodes {
+        if graphic.contains_key(&node) {
+            continue;
+        }
+
+        let placed: Vec<Pos2> = graph
+            .get_adj(&node)
+            .iter()
+            .chain(incoming.get(&node).into_iter().flatten())
+            .filter_map(|other| graphic.get(other).copied())
+            .collect();
+
+        let mut pos = if placed.is_empty() {
+            top += 100.0;
+            Pos2::new(right, top - 100.0)
+        } else {
+            let sum = placed
+                .iter()
+                .fold(Vec2::ZERO, |acc, pos| acc + pos.to_vec2());
+            (sum / placed.len() as f32).to_pos2() + Vec2::new(0.0, 100.0)
+        };
+
+        // step sideways until the spot is free
+        while occupied.near(pos) {
+            pos.x += 100.0;
+        }
+
+        occupied.insert(pos);
+        graphic.insert(node, pos);
+    }
+
+    graphic
+}
+
+//---------------------------------------------------------------------------//
+
+/// Positions bucketed into cells as wide as the spacing kept between nodes,
+/// so checking for a close neighbour only looks at the cells around a point
+struct Occupancy {
+    cells: HashMap<(i32, i32), Vec<Pos2>>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Occupancy {
+    /// Closest two nodes can be
+    const SPACING: f32 = 50.0;
+
+    //-----------------------------------------------------------------------//
+
+    fn new<'a>(positions: impl Iterator<Item = &'a Pos2>) -> Self {
+        let mut res = Self {
+            cells: HashMap::new(),
+        };
+        for pos in positions {
+            res.insert(*pos);
+        }
+        res
+    }
+
+    //-----------------------------------------------------------------------//
+
+    fn cell(pos: Pos2) -> (i32, i32) {
+        (
+            (pos.x / Self::SPACING).floor() as i32,
+            (pos.y / Self::SPACING).floor() as i32,
+        )
+    }
+
+    //-----------------------------------------------------------------------//
+
+    fn insert(&mut self, pos: Pos2) {
+        self.cells.entry(Self::cell(pos)).or_default().push(pos);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    /// Whether a position closer than the spacing is taken
+    fn near(&self, pos: Pos2) -> bool {
+        let (x, y) = Self::cell(pos);
+        (x - 1..=x + 1)
+            .flat_map(|x| (y - 1..=y + 1).map(move |y| (x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .any(|other| other.distance(pos) < Self::SPACING)
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -0,0 +1,199 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use egui::Vec2;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Cells smaller than this stop splitting, so coincident points still terminate
+const MIN_SIZE: f32 = 1e-3;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Barnes–Hut quadtree over a set of unit mass points
+///
+/// Distant groups of points are approximated by their centre of mass, which
+/// makes summing the repulsion on every point O(n log n) instead of O(n²)
+pub struct QuadTree {
+    cells: Vec<Cell>,
+}
+
+//---------------------------------------------------------------------------//
+
+struct Cell {
+    center_of_mass: Vec2,
+    mass: f32,
+    size: f32,
+    children: Vec<usize>,
+    /// Points in a leaf cell
+    bodies: Vec<usize>,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+impl QuadTree {
+    pub fn new(points: &[Vec2]) -> Self {
+        let mut tree = Self { cells: vec![] };
+
+        if points.is_empty() {
+            return tree;
+        }
+
+        let (min, max) = points.iter().fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), p| (min.min(*p), max.max(*p)),
+        );
+        let size = (max - min).max_elem().max(MIN_SIZE);
+
+        tree.build(points, (0..points.len()).collect(), (min + max) / 2.0, size);
+        tree
+    }
+
+    //-----------------------------------------------------------------------//
+
+    /// Builds the cell covering the square at `center`, returns its index
+    fn build(&mut self, points: &[Vec2], bodies: Vec<usize>, center: Vec2, size: f32) -> usize {
+        let mass = bodies.len() as f32;
+        let center_of_mass = bodies.iter().fold(Vec2::ZERO, |acc, i| acc + points[*i]) / mass;
+
+        let index = self.cells.len();
+        self.cells.push(Cell {
+            center_of_mass,
+            mass,
+            size,
+            children: vec![],
+            bodies: vec![],
+        });
+
+        if bodies.len() == 1 || size <= MIN_SIZE {
+            self.cells[index].bodies = bodies;
+            return index;
+        }
+
+        let mut quadrants: [Vec<usize>; 4] = Default::default();
+        for i in bodies {
+            let p = points[i];
+            let quadrant = usize::from(p.x >= center.x) + 2 * usize::from(p.y >= center.y);
+            quadrants[quadrant].push(i);
+        }
+
+        let quarter = size / 4.0;
+        for (quadrant, bodies) in quadrants.into_iter().enumerate() {
+            if bodies.is_empty() {
+                continue;
+            }
+
+            let offset = Vec2::new(
+                if quadrant % 2 == 1 { quarter } else { -quarter },
+                if quadrant >= 2 { quarter } else { -quarter },
+            );
+            let child = self.build(points, bodies, center + offset, size / 2.0);
+            self.cells[index].children.push(child);
+        }
+
+        index
+    }
+
+    //-----------------------------------------------------------------------//
+
+    /// Sum of `strength * diff / dist²` pushing point `i` away from all others
+    ///
+    /// Cells that look smaller than `theta` from the point are treated as one
+    /// body
+    pub fn repulsion(&self, points: &[Vec2], i: usize, theta: f32, strength: f32) -> Vec2 {
+        let mut force = Vec2::ZERO;
+
+        if self.cells.is_empty() {
+            return force;
+        }
+
+        let point = points[i];
+        let mut stack = vec![0];
+
+        while let Some(index) = stack.pop() {
+            let cell = &self.cells[index];
+
+            if cell.children.is_empty() {
+                for &other in cell.bodies.iter().filter(|other| **other != i) {
+                    force += push(point, points[other], 1.0, strength, i);
+                }
+                continue;
+            }
+
+            let dist = (point - cell.center_of_mass).length();
+            if cell.size < theta * dist {
+                force += push(point, cell.center_of_mass, cell.mass, strength, i);
+            } else {
+                stack.extend(&cell.children);
+            }
+        }
+
+        force
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+fn push(point: Vec2, other: Vec2, mass: f32, strength: f32, i: usize) -> Vec2 {
+    let diff = point - other;
+    let dist_sq = diff.length_sq();
+
+    if dist_sq < 1e-4 {
+        // nudge coincident points apart in a direction that depends on the point
+        return Vec2::angled(i as f32) * mass;
+    }
+
+    diff * (strength * mass / dist_sq)
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn coincident_points_still_push_apart() {
+        let points = vec![Vec2::new(3.0, 3.0); 50];
+        let tree = QuadTree::new(&points);
+
+        let first = tree.repulsion(&points, 0, 0.5, 1.0);
+        let second = tree.repulsion(&points, 1, 0.5, 1.0);
+
+        assert!(first.is_finite() && second.is_finite());
+        assert!(first.length() > 0.0);
+        assert_ne!(first, second);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn exact_without_approximation() {
+        let points: Vec<Vec2> = (0..20)
+            .map(|i| Vec2::new((i * 7 % 11) as f32, (i * 5 % 13) as f32) * 10.0)
+            .collect();
+        let tree = QuadTree::new(&points);
+
+        for i in 0..points.len() {
+            let expected = (0..points.len())
+                .filter(|j| *j != i)
+                .fold(Vec2::ZERO, |acc, j| {
+                    acc + push(points[i], points[j], 1.0, 2.0, i)
+                });
+            let force = tree.repulsion(&points, i, 0.0, 2.0);
+
+            assert!((force - expected).length() <= 1e-3 * expected.length().max(1.0));
+        }
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn no_points() {
+        let tree = QuadTree::new(&[]);
+        assert!(tree.cells.is_empty());
+    }
+
+    //-----------------------------------------------------------------------//
+}
@@ -0,0 +1,199 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use std::f32::consts::TAU;
+
+use cs_240_library::data_structures::graphs::{
+    weighted_graph::WeightedGraph, IDefiniteGraph, IGraph,
+};
+use egui::{
+    ahash::{HashMap, HashSet},
+    Pos2, Vec2,
+};
+
+use super::{bfs, centered, neighbours, Graphic};
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Breadth-first tree around `root`, one ring per level
+///
+/// Each subtree gets a slice of its parent's angle in proportion to how many
+/// leaves it has. Edge direction is ignored. Nodes not connected to the root
+/// get trees of their own, placed to the right
+///
+/// Without a root, the first node with no incoming edges is used
+pub fn layout(graph: &WeightedGraph<String, i32>, root: Option<&str>, spacing: f32) -> Graphic {
+    let neighbours = neighbours(graph);
+
+    let mut nodes = graph.get_all();
+    nodes.sort();
+
+    let root = root
+        .filter(|root| graph.contains(&root.to_string()))
+        .map(str::to_owned)
+        .or_else(|| default_root(graph, &nodes));
+
+    let mut seen = HashSet::default();
+    let mut graphic = Graphic::default();
+    let mut left = 300.0;
+
+    for start in root.into_iter().chain(nodes) {
+        if seen.contains(&start) {
+            continue;
+        }
+
+        let tree = bfs(&neighbours, &start, &mut seen);
+        let depth = tree.iter().map(|(_, _, depth)| *depth).max().unwrap_or(0);
+        let radius = depth as f32 * spacing;
+        let center = Pos2::new(left + radius, 300.0);
+
+        for (node, pos) in place(&tree) {
+            let pos = centered(&node, center + pos * spacing);
+            graphic.insert(node, pos);
+        }
+
+        left += 2.0 * radius + spacing;
+    }
+
+    graphic
+}
+
+//---------------------------------------------------------------------------//
+
+fn default_root(graph: &WeightedGraph<String, i32>, nodes: &[String]) -> Option<String> {
+    let targets: HashSet<String> = nodes
+        .iter()
+        .flat_map(|node| {
+            graph
+                .get_adj(node)
+                .into_iter()
+                .filter(move |adj| adj != node)
+        })
+        .collect();
+
+    nodes
+        .iter()
+        .find(|node| !targets.contains(*node))
+        .or(nodes.first())
+        .cloned()
+}
+
+//---------------------------------------------------------------------------//
+
+/// Offsets from the root in units of ring spacing
+fn place(tree: &[(String, Option<String>, usize)]) -> Vec<(String, Vec2)> {
+    let mut children: HashMap<&String, Vec<&String>> = HashMap::default();
+    for (node, parent, _) in tree {
+        if let Some(parent) = parent {
+            children.entry(parent).or_default().push(node);
+        }
+    }
+
+    // children come after their parents in breadth-first order
+    let mut leaves: HashMap<&String, f32> = HashMap::default();
+    for (node, _, _) in tree.iter().rev() {
+        let count = children
+            .get(node)
+            .map(|children| children.iter().map(|child| leaves[child]).sum())
+            .unwrap_or(1.0);
+        leaves.insert(node, count);
+    }
+
+    let mut ranges: HashMap<&String, (f32, f32)> = HashMap::default();
+    let mut res = vec![];
+
+    for (node, parent, depth) in tree {
+        let (start, end) = match parent {
+            None => (0.0, TAU),
+            Some(_) => ranges[node],
+        };
+
+        let pos = if *depth == 0 {
+            Vec2::ZERO
+        } else {
+            Vec2::angled((start + end) / 2.0) * *depth as f32
+        };
+        res.push((node.clone(), pos));
+
+        let mut from = start;
+        for child in children.get(node).into_iter().flatten() {
+            let share = (end - start) * leaves[child] / leaves[node];
+            ranges.insert(child, (from, from + share));
+            from += share;
+        }
+    }
+
+    res
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graphs, layout::assert_spread_out, render};
+
+    //-----------------------------------------------------------------------//
+
+    /// a -> b, a -> c, a -> d, b -> e, and f on its own
+    fn graph() -> WeightedGraph<String, i32> {
+        let edges = [("a", "b"), ("a", "c"), ("a", "d"), ("b", "e")]
+            .map(|(from, to)| (from.to_owned(), to.to_owned(), 1))
+            .to_vec();
+        graphs::build(
+            ["a", "b", "c", "d", "e", "f"].map(String::from).to_vec(),
+            edges,
+        )
+    }
+
+    //-----------------------------------------------------------------------//
+
+    fn center(graphic: &Graphic, node: &str) -> Pos2 {
+        render::node_rect(node, graphic[node]).center()
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn root_goes_in_the_middle_of_its_rings() {
+        let graph = graph();
+        let graphic = layout(&graph, None, 100.0);
+        assert_spread_out(&graph, &graphic);
+
+        // a has no incoming edges, so it is the root
+        let root = center(&graphic, "a");
+        for (node, ring) in [("b", 1.0), ("c", 1.0), ("d", 1.0), ("e", 2.0)] {
+            let dist = center(&graphic, node).distance(root);
+            assert!(
+                (dist - ring * 100.0).abs() < 0.01,
+                "{} is {} out",
+                node,
+                dist
+            );
+        }
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn any_node_can_be_the_root() {
+        let graph = graph();
+        let graphic = layout(&graph, Some("b"), 100.0);
+        assert_spread_out(&graph, &graphic);
+
+        let root = center(&graphic, "b");
+        for (node, ring) in [("a", 1.0), ("e", 1.0), ("c", 2.0), ("d", 2.0)] {
+            let dist = center(&graphic, node).distance(root);
+            assert!(
+                (dist - ring * 100.0).abs() < 0.01,
+                "{} is {} out",
+                node,
+                dist
+            );
+        }
+
+        // f gets a tree of its own, out to the right
+        assert!(center(&graphic, "f").x > root.x + 200.0);
+    }
+
+    //-----------------------------------------------------------------------//
+}
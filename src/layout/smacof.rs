@@ -0,0 +1,343 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use cs_240_library::data_structures::graphs::{
+    weighted_graph::WeightedGraph, IDefiniteGraph, IGraph, IWeightedGraph,
+};
+use egui::{ahash::HashMap, Vec2};
+
+use super::{centered, spiral, Graphic, Monitor};
+use crate::render;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Tuning for stress majorization
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Params {
+    /// Ideal length of a weight 1 edge, heavier edges are longer
+    pub edge_length: f32,
+    pub max_iterations: u32,
+    /// Stop once an iteration lowers the stress by less than this fraction
+    pub tolerance: f32,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            edge_length: 100.0,
+            max_iterations: 300,
+            tolerance: 1e-4,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Places the nodes on a spiral and then moves them with `simulate`
+///
+/// Nodes in `pinned` start and stay at their pinned position
+pub fn layout(
+    graph: &WeightedGraph<String, i32>,
+    pinned: &Graphic,
+    params: &Params,
+    monitor: &mut dyn Monitor,
+) -> Graphic {
+    let mut graphic = spiral(graph, pinned, params.edge_length);
+
+    simulate(graph, &mut graphic, pinned, params, monitor);
+
+    graphic
+}
+
+//---------------------------------------------------------------------------//
+
+/// Stress majorization (SMACOF), starting from the current positions
+///
+/// Tries to make the distance between every pair of nodes match their
+/// shortest path distance. Needs memory quadratic in the number of nodes, so
+/// it is meant for graphs of up to a few thousand nodes
+pub fn simulate(
+    graph: &WeightedGraph<String, i32>,
+    graphic: &mut Graphic,
+    pinned: &Graphic,
+    params: &Params,
+    monitor: &mut dyn Monitor,
+) {
+    let nodes = placed(graph, graphic);
+    let dist = distances(graph, &nodes, params.edge_length);
+
+    let fixed: Vec<bool> = nodes.iter().map(|n| pinned.contains_key(n)).collect();
+    let mut points = centers(&nodes, graphic);
+
+    let mut stress = stress_of(&points, &dist);
+
+    for iteration in 0..params.max_iterations {
+        // each node moves to where it best fits the current positions of all
+        // the others, which never increases the stress
+        for i in 0..points.len() {
+            if fixed[i] {
+                continue;
+            }
+
+            let mut sum = Vec2::ZERO;
+            let mut total = 0.0;
+
+            for j in 0..points.len() {
+                if i == j {
+                    continue;
+                }
+
+                let weight = 1.0 / (dist[i][j] * dist[i][j]);
+                let diff = points[i] - points[j];
+                let len = diff.length();
+
+                let target = if len > 0.0 {
+                    points[j] + diff * (dist[i][j] / len)
+                } else {
+                    points[j]
+                };
+
+                sum += target * weight;
+                total += weight;
+            }
+
+            if total > 0.0 {
+                points[i] = sum / total;
+            }
+        }
+
+        let next = stress_of(&points, &dist);
+        let settled = stress - next <= params.tolerance * stress;
+        stress = next;
+
+        if settled {
+            break;
+        }
+
+        let positions = || {
+            nodes
+                .iter()
+                .zip(&points)
+                .map(|(node, point)| (node.clone(), centered(node, point.to_pos2())))
+                .collect()
+        };
+        let fraction = (iteration + 1) as f32 / params.max_iterations as f32;
+        if !monitor.report(fraction, Some(&positions)) {
+            break;
+        }
+    }
+
+    for ((node, point), fixed) in nodes.iter().zip(points).zip(fixed) {
+        if !fixed {
+            graphic.insert(node.clone(), centered(node, point.to_pos2()));
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+/// How far the layout is from matching shortest path distances, 0 is a
+/// perfect match
+pub fn stress(graph: &WeightedGraph<String, i32>, graphic: &Graphic, edge_length: f32) -> f32 {
+    let nodes = placed(graph, graphic);
+    let dist = distances(graph, &nodes, edge_length);
+
+    stress_of(&centers(&nodes, graphic), &dist)
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Nodes of the graph that have a position
+fn placed(graph: &WeightedGraph<String, i32>, graphic: &Graphic) -> Vec<String> {
+    let mut nodes: Vec<String> = graph
+        .get_all()
+        .into_iter()
+        .filter(|node| graphic.contains_key(node))
+        .collect();
+    nodes.sort();
+    nodes
+}
+
+//---------------------------------------------------------------------------//
+
+/// Middle of each node's box, which is what edges are drawn between
+fn centers(nodes: &[String], graphic: &Graphic) -> Vec<Vec2> {
+    nodes
+        .iter()
+        .map(|node| render::node_rect(node, graphic[node]).center().to_vec2())
+        .collect()
+}
+
+//---------------------------------------------------------------------------//
+
+/// Sum over every pair of `(distance - ideal)² / ideal²`
+fn stress_of(points: &[Vec2], dist: &[Vec<f32>]) -> f32 {
+    let mut stress = 0.0;
+
+    for i in 0..points.len() {
+        for j in i + 1..points.len() {
+            let len = (points[i] - points[j]).length();
+            let ideal = dist[i][j];
+            stress += (len - ideal) * (len - ideal) / (ideal * ideal);
+        }
+    }
+
+    stress
+}
+
+//---------------------------------------------------------------------------//
+
+/// Shortest path distance between every pair of `nodes`, ignoring edge
+/// direction
+///
+/// Weights below 1 count as 1. Pairs in different components are put one edge
+/// further apart than the furthest connected pair
+fn distances(
+    graph: &WeightedGraph<String, i32>,
+    nodes: &[String],
+    edge_length: f32,
+) -> Vec<Vec<f32>> {
+    let index: HashMap<&String, usize> = nodes.iter().enumerate().map(|(i, n)| (n, i)).collect();
+
+    let mut adj: Vec<Vec<(usize, u64)>> = vec![vec![]; nodes.len()];
+    for (i, node) in nodes.iter().enumerate() {
+        for (other, weight) in graph.get_adj_weighted(node) {
+            if let Some(&j) = index.get(&other) {
+                let weight = weight.max(1) as u64;
+                adj[i].push((j, weight));
+                adj[j].push((i, weight));
+            }
+        }
+    }
+
+    let hops: Vec<Vec<u64>> = (0..nodes.len()).map(|i| dijkstra(&adj, i)).collect();
+
+    let furthest = hops
+        .iter()
+        .flatten()
+        .filter(|d| **d != u64::MAX)
+        .max()
+        .copied()
+        .unwrap_or(0);
+
+    hops.into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|d| {
+                    let d = if d == u64::MAX { furthest + 1 } else { d };
+                    d.max(1) as f32 * edge_length
+                })
+                .collect()
+        })
+        .collect()
+}
+
+//---------------------------------------------------------------------------//
+
+fn dijkstra(adj: &[Vec<(usize, u64)>], start: usize) -> Vec<u64> {
+    let mut dist = vec![u64::MAX; adj.len()];
+    let mut queue = BinaryHeap::new();
+
+    dist[start] = 0;
+    queue.push(Reverse((0, start)));
+
+    while let Some(Reverse((d, node))) = queue.pop() {
+        if d > dist[node] {
+            continue;
+        }
+
+        for &(other, weight) in &adj[node] {
+            let next = d + weight;
+            if next < dist[other] {
+                dist[other] = next;
+                queue.push(Reverse((next, other)));
+            }
+        }
+    }
+
+    dist
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+
+    use super::*;
+    use crate::graphs;
+
+    //-----------------------------------------------------------------------//
+
+    /// a -> b -> c
+    fn path() -> WeightedGraph<String, i32> {
+        graphs::build(
+            ["a", "b", "c"].map(String::from).to_vec(),
+            vec![
+                ("a".to_owned(), "b".to_owned(), 1),
+                ("b".to_owned(), "c".to_owned(), 1),
+            ],
+        )
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn stress_is_zero_for_a_perfect_layout() {
+        let graphic: Graphic = [("a", 0.0), ("b", 100.0), ("c", 200.0)]
+            .map(|(node, x)| (node.to_owned(), Pos2::new(x, 0.0)))
+            .into_iter()
+            .collect();
+
+        assert!(stress(&path(), &graphic, 100.0) < 1e-6);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn stress_is_measured_between_centres() {
+        let graph = graphs::build(
+            vec!["a".to_owned(), "a much longer name".to_owned()],
+            vec![("a".to_owned(), "a much longer name".to_owned(), 1)],
+        );
+        let graphic: Graphic = [("a", 0.0), ("a much longer name", 100.0)]
+            .map(|(node, x)| (node.to_owned(), centered(node, Pos2::new(x, 0.0))))
+            .into_iter()
+            .collect();
+
+        assert!(stress(&graph, &graphic, 100.0) < 1e-6);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn lays_a_path_out_straight() {
+        let graph = path();
+        let graphic = layout(&graph, &Graphic::default(), &Params::default(), &mut ());
+        let dist = |a: &str, b: &str| graphic[a].distance(graphic[b]);
+
+        assert!((dist("a", "b") - 100.0).abs() < 5.0);
+        assert!((dist("b", "c") - 100.0).abs() < 5.0);
+        assert!((dist("a", "c") - 200.0).abs() < 5.0);
+        assert!(stress(&graph, &graphic, 100.0) < 0.01);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn pinned_nodes_stay_put() {
+        let pinned: Graphic = [("b".to_owned(), Pos2::new(-500.0, 40.0))]
+            .into_iter()
+            .collect();
+        let graphic = layout(&path(), &pinned, &Params::default(), &mut ());
+
+        assert_eq!(graphic["b"], Pos2::new(-500.0, 40.0));
+        assert!((graphic["a"].distance(graphic["b"]) - 100.0).abs() < 5.0);
+    }
+
+    //-----------------------------------------------------------------------//
+}
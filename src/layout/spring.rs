@@ -0,0 +1,155 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use cs_240_library::data_structures::graphs::{
+    weighted_graph::WeightedGraph, IDefiniteGraph, IGraph, IWeightedGraph,
+};
+use egui::{ahash::HashMap, Vec2};
+
+use super::{quadtree::QuadTree, spiral, Graphic, Monitor};
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Tuning for the force-directed layout
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Params {
+    /// Relaxed length of a weight 1 edge, heavier edges are longer
+    pub edge_length: f32,
+    /// How hard nodes push each other apart
+    pub repulsion: f32,
+    /// Pull towards the centre, keeps disconnected parts together
+    pub gravity: f32,
+    /// Barnes–Hut accuracy, 0 is exact and higher is faster
+    pub theta: f32,
+    /// Fraction of the temperature kept after each step
+    pub cooling: f32,
+    pub max_steps: u32,
+    /// Stop once no node moves further than this in a step
+    pub tolerance: f32,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            edge_length: 100.0,
+            repulsion: 1.0,
+            gravity: 0.05,
+            theta: 0.8,
+            cooling: 0.97,
+            max_steps: 500,
+            tolerance: 0.5,
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Places the nodes on a spiral and then relaxes them with `simulate`
+///
+/// Nodes in `pinned` start and stay at their pinned position
+pub fn layout(
+    graph: &WeightedGraph<String, i32>,
+    pinned: &Graphic,
+    params: &Params,
+    monitor: &mut dyn Monitor,
+) -> Graphic {
+    let mut graphic = spiral(graph, pinned, params.edge_length);
+
+    simulate(graph, &mut graphic, pinned, params, monitor);
+
+    graphic
+}
+
+//---------------------------------------------------------------------------//
+
+/// Fruchterman–Reingold style relaxation with Barnes–Hut repulsion
+///
+/// Each step may move a node at most the current temperature, which cools
+/// down every step. Stops early once the layout has settled
+pub fn simulate(
+    graph: &WeightedGraph<String, i32>,
+    graphic: &mut Graphic,
+    pinned: &Graphic,
+    params: &Params,
+    monitor: &mut dyn Monitor,
+) {
+    let nodes: Vec<String> = graph
+        .get_all()
+        .into_iter()
+        .filter(|node| graphic.contains_key(node))
+        .collect();
+
+    let index: HashMap<&String, usize> = nodes.iter().enumerate().map(|(i, n)| (n, i)).collect();
+
+    let mut edges = vec![];
+    for (i, node) in nodes.iter().enumerate() {
+        for (adj, weight) in graph.get_adj_weighted(node) {
+            if let Some(&j) = index.get(&adj) {
+                if i != j {
+                    edges.push((i, j, params.edge_length * weight.max(1) as f32));
+                }
+            }
+        }
+    }
+
+    let fixed: Vec<bool> = nodes.iter().map(|n| pinned.contains_key(n)).collect();
+    let mut points: Vec<Vec2> = nodes.iter().map(|n| graphic[n].to_vec2()).collect();
+    let mut forces = vec![Vec2::ZERO; points.len()];
+
+    let strength = params.repulsion * params.edge_length * params.edge_length;
+    let mut temperature = params.edge_length;
+
+    for round in 0..params.max_steps {
+        let tree = QuadTree::new(&points);
+        let centre = points.iter().fold(Vec2::ZERO, |acc, p| acc + *p) / points.len() as f32;
+
+        for (i, force) in forces.iter_mut().enumerate() {
+            *force = tree.repulsion(&points, i, params.theta, strength)
+                + (centre - points[i]) * params.gravity;
+        }
+
+        for &(i, j, length) in &edges {
+            let diff = points[j] - points[i];
+            let pull = diff * (diff.length() / length);
+            forces[i] += pull;
+            forces[j] -= pull;
+        }
+
+        let mut moved: f32 = 0.0;
+        for (i, force) in forces.iter().enumerate() {
+            if fixed[i] || force.length_sq() == 0.0 {
+                continue;
+            }
+
+            let step = force.normalized() * force.length().min(temperature);
+            points[i] += step;
+            moved = moved.max(step.length());
+        }
+
+        temperature *= params.cooling;
+
+        if moved < params.tolerance {
+            break;
+        }
+
+        let positions = || {
+            nodes
+                .iter()
+                .cloned()
+                .zip(points.iter().map(|point| point.to_pos2()))
+                .collect()
+        };
+        let fraction = (round + 1) as f32 / params.max_steps as f32;
+        if !monitor.report(fraction, Some(&positions)) {
+            break;
+        }
+    }
+
+    for (node, point) in nodes.iter().zip(points) {
+        graphic.insert(node.clone(), point.to_pos2());
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
@@ -4,20 +4,26 @@ use std::fs;
 
 use anyhow::Result;
 use clap::Parser;
-use cli::{Config, ExampleArgs, InteractiveArgs, NewArgs};
+use cli::{Config, ExampleArgs, InteractiveArgs, LayoutArgs, NewArgs, PngArgs, RenderArgs};
 use cs_240_library::data_structures::graphs::{
-    directed_graph::DirectedGraph, undirected_graph::UndirectedGraph, IGraph, IGraphEdgeMut,
-    IGraphMut,
+    directed_graph::DirectedGraph, undirected_graph::UndirectedGraph,
+    weighted_graph::WeightedGraph, IGraphEdgeMut, IGraphEdgeWeightedMut, IGraphMut,
 };
 
+use document::{Document, GraphKind, Layout};
 use gui::App;
-use interactive::interactive;
+use interactive::{interactive, ReplGraph};
 
 ///////////////////////////////////////////////////////////////////////////////
 
 mod cli;
+mod document;
+mod graphs;
 mod gui;
 mod interactive;
+mod layout;
+mod render;
+mod traversal;
 
 ///////////////////////////////////////////////////////////////////////////////
 
@@ -31,22 +37,54 @@ fn main() -> Result<()> {
         cli::Commands::Example(args) => example(args),
         cli::Commands::Gui => Ok(gui()),
         cli::Commands::Open(args) => open(args),
+        cli::Commands::Render(args) => render(args),
+        cli::Commands::Png(args) => png(args),
     }
 }
 
 ///////////////////////////////////////////////////////////////////////////////
 
 fn new(args: NewArgs) -> Result<()> {
-    let mut graph = DirectedGraph::new();
+    let contents = match args.kind {
+        GraphKind::Directed => {
+            let mut graph = DirectedGraph::new();
+
+            graph.insert_node("node-1".to_owned());
+            graph.insert_node("node-2".to_owned());
+            graph.insert_node("node-3".to_owned());
 
-    graph.insert_node("node-1");
-    graph.insert_node("node-2");
-    graph.insert_node("node-3");
+            graph.insert_edge("node-1".to_owned(), "node-2".to_owned());
+            graph.insert_edge("node-1".to_owned(), "node-3".to_owned());
 
-    graph.insert_edge("node-1", "node-2");
-    graph.insert_edge("node-1", "node-3");
+            serde_yaml::to_string(&Document::new(graph))?
+        }
+        GraphKind::Undirected => {
+            let mut graph = UndirectedGraph::new();
+
+            graph.insert_node("node-1".to_owned());
+            graph.insert_node("node-2".to_owned());
+            graph.insert_node("node-3".to_owned());
+
+            graph.insert_edge("node-1".to_owned(), "node-2".to_owned());
+            graph.insert_edge("node-1".to_owned(), "node-3".to_owned());
+
+            serde_yaml::to_string(&Document::new(graph))?
+        }
+        GraphKind::Weighted => {
+            let mut graph = WeightedGraph::new();
 
-    fs::write(args.path, serde_yaml::to_string(&graph)?)?;
+            graph.insert_node("node-1".to_owned());
+            graph.insert_node("node-2".to_owned());
+            graph.insert_node("node-3".to_owned());
+
+            graph.insert_edge_weighted("node-1".to_owned(), "node-2".to_owned(), 1);
+            graph.insert_edge_weighted("node-1".to_owned(), "node-3".to_owned(), 2);
+
+            serde_yaml::to_string(&Document::new(graph))?
+        }
+    };
+
+    fs::write(args.path, contents)?;
 
     Ok(())
 }
@@ -55,15 +93,39 @@ fn new(args: NewArgs) -> Result<()> {
 
 pub fn open(args: InteractiveArgs) -> Result<()> {
     let file_contents = fs::read_to_string(args.path.clone())?;
-    let mut graph: DirectedGraph<String> = serde_yaml::from_str(&file_contents)?;
 
-    interactive(&mut graph)?;
+    let contents = match document::kind(&file_contents)? {
+        GraphKind::Directed => open_as::<DirectedGraph<String>>(&file_contents)?,
+        GraphKind::Undirected => open_as::<UndirectedGraph<String>>(&file_contents)?,
+        GraphKind::Weighted => open_as::<WeightedGraph<String, i32>>(&file_contents)?,
+    };
 
-    fs::write(args.path, serde_yaml::to_string(&graph)?)?;
+    fs::write(args.path, contents)?;
 
     Ok(())
 }
 
+//...........................................................................//
+
+/// Runs interactive mode on the file and returns the updated file contents
+fn open_as<G: ReplGraph>(file_contents: &str) -> Result<String> {
+    let mut doc: Document<G> = Document::parse(file_contents)?;
+
+    interactive(&mut doc.graph)?;
+
+    // drop positions of removed nodes, new ones get placed on next render
+    if let Some(layout) = &doc.layout {
+        doc.layout = Some(Layout::new(
+            &doc.graph,
+            &doc.graphic(),
+            &layout.pinned,
+            layout.view.clone(),
+        ));
+    }
+
+    Ok(serde_yaml::to_string(&doc)?)
+}
+
 //---------------------------------------------------------------------------//
 
 fn example(args: ExampleArgs) -> Result<()> {
@@ -72,72 +134,19 @@ fn example(args: ExampleArgs) -> Result<()> {
 
     match args.example {
         cli::Example::Pathfinding => {
-            let mut graph = UndirectedGraph::new();
-            graph.insert_node("bellingham".to_owned());
-            graph.insert_node("seattle".to_owned());
-            graph.insert_node("everett".to_owned());
-            graph.insert_node("arlington".to_owned());
-            graph.insert_node("mt-vernon".to_owned());
-            graph.insert_node("ferndale".to_owned());
-            graph.insert_node("anacortes".to_owned());
-            graph.insert_node("edmonds".to_owned());
-            graph.insert_node("redmond".to_owned());
-            graph.insert_node("seatac".to_owned());
-            graph.insert_node("tacoma".to_owned());
-            graph.insert_node("vancouver".to_owned());
-            graph.insert_node("bothell".to_owned());
-
-            graph.insert_edge("mt-vernon".to_owned(), "bellingham".to_owned());
-            graph.insert_edge("bellingham".to_owned(), "ferndale".to_owned());
-            graph.insert_edge("vancouver".to_owned(), "ferndale".to_owned());
-            graph.insert_edge("mt-vernon".to_owned(), "anacortes".to_owned());
-            graph.insert_edge("mt-vernon".to_owned(), "arlington".to_owned());
-            graph.insert_edge("arlington".to_owned(), "everett".to_owned());
-            graph.insert_edge("everett".to_owned(), "edmonds".to_owned());
-            graph.insert_edge("everett".to_owned(), "bothell".to_owned());
-            graph.insert_edge("edmonds".to_owned(), "bothell".to_owned());
-            graph.insert_edge("edmonds".to_owned(), "seattle".to_owned());
-            graph.insert_edge("bothell".to_owned(), "seattle".to_owned());
-            graph.insert_edge("bothell".to_owned(), "redmond".to_owned());
-            graph.insert_edge("seattle".to_owned(), "seatac".to_owned());
-            graph.insert_edge("seatac".to_owned(), "tacoma".to_owned());
+            let mut graph = pathfinding();
 
             println!("Try using the `route` command");
             println!("For example: `route Bellingham Redmond`");
 
             println!();
 
-            let mut graph = DirectedGraph::from(graph);
-
             interactive(&mut graph)?;
 
             Ok(())
         }
         cli::Example::JobScheduling => {
-            let mut graph = DirectedGraph::new();
-            graph.insert_node("task-1".to_owned());
-            graph.insert_node("task-2".to_owned());
-            graph.insert_node("task-3".to_owned());
-            graph.insert_node("task-4".to_owned());
-            graph.insert_node("task-5".to_owned());
-            graph.insert_node("task-6".to_owned());
-            graph.insert_node("task-7".to_owned());
-            graph.insert_node("task-8".to_owned());
-            graph.insert_node("task-9".to_owned());
-            graph.insert_node("task-10".to_owned());
-
-            graph.insert_edge("task-1".to_owned(), "task-2".to_owned());
-            graph.insert_edge("task-1".to_owned(), "task-3".to_owned());
-            graph.insert_edge("task-1".to_owned(), "task-4".to_owned());
-
-            graph.insert_edge("task-2".to_owned(), "task-5".to_owned());
-            graph.insert_edge("task-2".to_owned(), "task-6".to_owned());
-
-            graph.insert_edge("task-3".to_owned(), "task-7".to_owned());
-
-            graph.insert_edge("task-7".to_owned(), "task-9".to_owned());
-            graph.insert_edge("task-7".to_owned(), "task-10".to_owned());
-            graph.insert_edge("task-6".to_owned(), "task-8".to_owned());
+            let mut graph = job_scheduling();
 
             println!("Try using the `schedule` command");
             println!();
@@ -149,6 +158,132 @@ fn example(args: ExampleArgs) -> Result<()> {
     }
 }
 
+//---------------------------------------------------------------------------//
+
+/// Cities around Puget Sound, for trying out `route`
+fn pathfinding() -> UndirectedGraph<String> {
+    let mut graph = UndirectedGraph::new();
+    graph.insert_node("bellingham".to_owned());
+    graph.insert_node("seattle".to_owned());
+    graph.insert_node("everett".to_owned());
+    graph.insert_node("arlington".to_owned());
+    graph.insert_node("mt-vernon".to_owned());
+    graph.insert_node("ferndale".to_owned());
+    graph.insert_node("anacortes".to_owned());
+    graph.insert_node("edmonds".to_owned());
+    graph.insert_node("redmond".to_owned());
+    graph.insert_node("seatac".to_owned());
+    graph.insert_node("tacoma".to_owned());
+    graph.insert_node("vancouver".to_owned());
+    graph.insert_node("bothell".to_owned());
+
+    graph.insert_edge("mt-vernon".to_owned(), "bellingham".to_owned());
+    graph.insert_edge("bellingham".to_owned(), "ferndale".to_owned());
+    graph.insert_edge("vancouver".to_owned(), "ferndale".to_owned());
+    graph.insert_edge("mt-vernon".to_owned(), "anacortes".to_owned());
+    graph.insert_edge("mt-vernon".to_owned(), "arlington".to_owned());
+    graph.insert_edge("arlington".to_owned(), "everett".to_owned());
+    graph.insert_edge("everett".to_owned(), "edmonds".to_owned());
+    graph.insert_edge("everett".to_owned(), "bothell".to_owned());
+    graph.insert_edge("edmonds".to_owned(), "bothell".to_owned());
+    graph.insert_edge("edmonds".to_owned(), "seattle".to_owned());
+    graph.insert_edge("bothell".to_owned(), "seattle".to_owned());
+    graph.insert_edge("bothell".to_owned(), "redmond".to_owned());
+    graph.insert_edge("seattle".to_owned(), "seatac".to_owned());
+    graph.insert_edge("seatac".to_owned(), "tacoma".to_owned());
+
+    graph
+}
+
+//---------------------------------------------------------------------------//
+
+/// Tasks that depend on each other, for trying out `schedule`
+fn job_scheduling() -> DirectedGraph<String> {
+    let mut graph = DirectedGraph::new();
+    graph.insert_node("task-1".to_owned());
+    graph.insert_node("task-2".to_owned());
+    graph.insert_node("task-3".to_owned());
+    graph.insert_node("task-4".to_owned());
+    graph.insert_node("task-5".to_owned());
+    graph.insert_node("task-6".to_owned());
+    graph.insert_node("task-7".to_owned());
+    graph.insert_node("task-8".to_owned());
+    graph.insert_node("task-9".to_owned());
+    graph.insert_node("task-10".to_owned());
+
+    graph.insert_edge("task-1".to_owned(), "task-2".to_owned());
+    graph.insert_edge("task-1".to_owned(), "task-3".to_owned());
+    graph.insert_edge("task-1".to_owned(), "task-4".to_owned());
+
+    graph.insert_edge("task-2".to_owned(), "task-5".to_owned());
+    graph.insert_edge("task-2".to_owned(), "task-6".to_owned());
+
+    graph.insert_edge("task-3".to_owned(), "task-7".to_owned());
+
+    graph.insert_edge("task-7".to_owned(), "task-9".to_owned());
+    graph.insert_edge("task-7".to_owned(), "task-10".to_owned());
+    graph.insert_edge("task-6".to_owned(), "task-8".to_owned());
+
+    graph
+}
+
+//---------------------------------------------------------------------------//
+
+fn render(args: RenderArgs) -> Result<()> {
+    let file_contents = fs::read_to_string(args.path)?;
+    let doc = document::load_weighted(&file_contents)?;
+
+    let graphic = place(&doc, &args.layout);
+
+    fs::write(args.output, render::svg(&doc.graph, doc.kind, &graphic)?)?;
+
+    Ok(())
+}
+
+//---------------------------------------------------------------------------//
+
+fn png(args: PngArgs) -> Result<()> {
+    let file_contents = fs::read_to_string(args.path)?;
+    let doc = document::load_weighted(&file_contents)?;
+
+    let graphic = place(&doc, &args.layout);
+    let scale = args.dpi.map(|dpi| dpi / 96.0).unwrap_or(args.scale);
+
+    fs::write(
+        args.output,
+        render::png(&render::svg(&doc.graph, doc.kind, &graphic)?, scale)?,
+    )?;
+
+    Ok(())
+}
+
+//---------------------------------------------------------------------------//
+
+/// Saved positions, or a fresh layout if one was asked for
+fn place(doc: &Document<WeightedGraph<String, i32>>, args: &LayoutArgs) -> layout::Graphic {
+    match args.layout {
+        Some(method) => {
+            let params = layout::Params {
+                method,
+                root: args.root.clone(),
+                ..Default::default()
+            };
+            let pinned = doc
+                .graphic()
+                .into_iter()
+                .filter(|(node, _)| {
+                    doc.layout
+                        .as_ref()
+                        .is_some_and(|layout| layout.pinned.contains(node))
+                })
+                .collect();
+
+            layout::compute(&doc.graph, &pinned, &params, &mut ())
+        }
+        None => layout::update(&doc.graph, &doc.graphic(), &Default::default(), &mut ()),
+    }
+}
+
 ///////////////////////////////////////////////////////////////////////////////
 
 fn gui() {
@@ -168,3 +303,33 @@ fn gui() {
 }
 
 ///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //-----------------------------------------------------------------------//
+
+    fn route(command: &str) -> String {
+        let mut out = vec![];
+        interactive::run_command(command, &mut pathfinding(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn documented_routes_resolve() {
+        // the hint printed by `example pathfinding`
+        let out = route("route Bellingham Redmond");
+        assert!(out.starts_with("\n- bellingham\n"), "{}", out);
+        assert!(out.ends_with("- bothell\n- redmond\n"), "{}", out);
+
+        // the README's example
+        let out = route("route Bellingham Seattle");
+        assert!(out.starts_with("\n- bellingham\n"), "{}", out);
+        assert!(out.ends_with("- seattle\n"), "{}", out);
+    }
+
+    //-----------------------------------------------------------------------//
+}
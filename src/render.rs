@@ -0,0 +1,326 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use anyhow::{Error, Result};
+use cs_240_library::data_structures::graphs::{
+    weighted_graph::WeightedGraph, IDefiniteGraph, IGraph,
+};
+use egui::{Pos2, Rect, Vec2};
+use resvg::{
+    tiny_skia,
+    usvg::{self, fontdb, TreeParsing, TreeTextToPath},
+};
+
+use crate::{document::GraphKind, graphs, layout::Graphic};
+
+///////////////////////////////////////////////////////////////////////////////
+
+const FONT_SIZE: f32 = 14.0;
+const CHAR_WIDTH: f32 = 7.5;
+const MARGIN: f32 = 8.0;
+const PADDING: f32 = 40.0;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Box drawn around a node's label, matching the canvas' node frames
+pub fn node_rect(name: &str, pos: Pos2) -> Rect {
+    let size = Vec2::new(
+        CHAR_WIDTH * name.chars().count() as f32 + 2.0 * MARGIN,
+        FONT_SIZE + 2.0 * MARGIN + 2.0,
+    );
+    Rect::from_min_size(pos, size)
+}
+
+//---------------------------------------------------------------------------//
+
+/// Moves `to` back along the segment until it sits on the border of `rect`
+pub fn clip_to_rect(from: Pos2, to: Pos2, rect: Rect) -> Pos2 {
+    let dir = to - from;
+    let half = rect.size() / 2.0;
+
+    let tx = if dir.x != 0.0 {
+        half.x / dir.x.abs()
+    } else {
+        f32::INFINITY
+    };
+    let ty = if dir.y != 0.0 {
+        half.y / dir.y.abs()
+    } else {
+        f32::INFINITY
+    };
+    let t = tx.min(ty).min(1.0);
+
+    to - dir * t
+}
+
+//---------------------------------------------------------------------------//
+
+/// How far edges with a reverse twin bow out to the side
+const BEND: f32 = 24.0;
+
+//...........................................................................//
+
+/// Path of an edge, with its ends on the node borders
+pub enum EdgeCurve {
+    /// Quadratic bezier: start, control, end
+    Bend([Pos2; 3]),
+    /// Cubic bezier looping over the top of a node
+    Loop([Pos2; 4]),
+}
+
+//...........................................................................//
+
+/// Edges with a reverse twin (`a→b` and `b→a`) bend to opposite sides so both
+/// stay visible
+pub fn edge_curve(from: Rect, to: Rect, self_loop: bool, twin: bool) -> EdgeCurve {
+    if self_loop {
+        let top = from.center_top();
+        return EdgeCurve::Loop([
+            top - Vec2::new(8.0, 0.0),
+            top + Vec2::new(-24.0, -36.0),
+            top + Vec2::new(24.0, -36.0),
+            top + Vec2::new(8.0, 0.0),
+        ]);
+    }
+
+    let (a, b) = (from.center(), to.center());
+    let dir = b - a;
+    let control = if twin && dir.length() > 0.0 {
+        a.lerp(b, 0.5) + dir.normalized().rot90() * BEND
+    } else {
+        a.lerp(b, 0.5)
+    };
+
+    EdgeCurve::Bend([
+        clip_to_rect(control, a, from),
+        control,
+        clip_to_rect(control, b, to),
+    ])
+}
+
+//...........................................................................//
+
+impl EdgeCurve {
+    /// Point at `t` in `0..=1` along the curve
+    pub fn sample(&self, t: f32) -> Pos2 {
+        let s = 1.0 - t;
+        match self {
+            EdgeCurve::Bend([a, c, b]) => {
+                (a.to_vec2() * s * s + c.to_vec2() * 2.0 * s * t + b.to_vec2() * t * t).to_pos2()
+            }
+            EdgeCurve::Loop([a, c1, c2, b]) => (a.to_vec2() * s * s * s
+                + c1.to_vec2() * 3.0 * s * s * t
+                + c2.to_vec2() * 3.0 * s * t * t
+                + b.to_vec2() * t * t * t)
+                .to_pos2(),
+        }
+    }
+
+    //-----------------------------------------------------------------------//
+
+    /// Where the arrow points and which way it is heading
+    pub fn tip(&self) -> (Pos2, Vec2) {
+        match self {
+            EdgeCurve::Bend([_, c, b]) => (*b, *b - *c),
+            EdgeCurve::Loop([_, _, c2, b]) => (*b, *b - *c2),
+        }
+    }
+
+    //-----------------------------------------------------------------------//
+
+    fn svg_path(&self) -> String {
+        match self {
+            EdgeCurve::Bend([a, c, b]) => {
+                format!("M {} {} Q {} {} {} {}", a.x, a.y, c.x, c.y, b.x, b.y)
+            }
+            EdgeCurve::Loop([a, c1, c2, b]) => format!(
+                "M {} {} C {} {} {} {} {} {}",
+                a.x, a.y, c1.x, c1.y, c2.x, c2.y, b.x, b.y
+            ),
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Renders the laid out graph as a standalone SVG document
+///
+/// Undirected graphs get one straight line per edge, without arrowheads
+pub fn svg(
+    graph: &WeightedGraph<String, i32>,
+    kind: GraphKind,
+    graphic: &Graphic,
+) -> Result<String> {
+    let mut rects = BTreeMap::new();
+    for node in graph.get_all() {
+        let pos = graphic
+            .get(&node)
+            .ok_or(Error::msg(format!("No position for node {}", node)))?;
+        rects.insert(node.clone(), node_rect(&node, *pos));
+    }
+
+    let bounds = rects
+        .values()
+        .fold(Rect::NOTHING, |acc, rect| acc.union(*rect))
+        .expand(PADDING);
+    let bounds = if bounds.is_positive() {
+        bounds
+    } else {
+        Rect::from_min_size(Pos2::ZERO, Vec2::splat(2.0 * PADDING))
+    };
+
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+        bounds.min.x,
+        bounds.min.y,
+        bounds.width(),
+        bounds.height(),
+        bounds.width(),
+        bounds.height()
+    )?;
+    writeln!(
+        out,
+        r##"<defs><marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="8" markerHeight="8" orient="auto-start-reverse"><path d="M 0 0 L 10 5 L 0 10 z" fill="#555"/></marker></defs>"##
+    )?;
+    writeln!(
+        out,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="white"/>"#,
+        bounds.min.x,
+        bounds.min.y,
+        bounds.width(),
+        bounds.height()
+    )?;
+
+    let directed = kind != GraphKind::Undirected;
+    let edges = if directed {
+        graphs::edges(graph)
+    } else {
+        graphs::undirected_edges(graph)
+    };
+    let curves: Vec<_> = edges
+        .iter()
+        .filter_map(|(from, to, weight)| {
+            let twin = directed && has_edge(graph, to, from);
+            let curve = edge_curve(*rects.get(from)?, *rects.get(to)?, from == to, twin);
+            Some((curve, weight))
+        })
+        .collect();
+    let marker = if directed {
+        r#" marker-end="url(#arrow)""#
+    } else {
+        ""
+    };
+
+    writeln!(out, r##"<g stroke="#555" stroke-width="1" fill="none">"##)?;
+    for (curve, _) in &curves {
+        writeln!(out, r#"<path d="{}"{}/>"#, curve.svg_path(), marker)?;
+    }
+    writeln!(out, "</g>")?;
+
+    writeln!(
+        out,
+        r##"<g font-family="Ubuntu, sans-serif" font-size="{}" fill="#555" text-anchor="middle">"##,
+        FONT_SIZE - 2.0
+    )?;
+    for (curve, weight) in &curves {
+        let mid = curve.sample(0.5);
+        writeln!(
+            out,
+            r#"<text x="{}" y="{}">{}</text>"#,
+            mid.x, mid.y, weight
+        )?;
+    }
+    writeln!(out, "</g>")?;
+
+    writeln!(
+        out,
+        r##"<g font-family="Ubuntu, sans-serif" font-size="{}" text-anchor="middle" dominant-baseline="central">"##,
+        FONT_SIZE
+    )?;
+    for (node, rect) in &rects {
+        writeln!(
+            out,
+            r##"<rect x="{}" y="{}" width="{}" height="{}" rx="{}" fill="white" stroke="#999"/>"##,
+            rect.min.x,
+            rect.min.y,
+            rect.width(),
+            rect.height(),
+            rect.height() / 2.0
+        )?;
+        writeln!(
+            out,
+            r#"<text x="{}" y="{}">{}</text>"#,
+            rect.center().x,
+            rect.center().y,
+            escape(node)
+        )?;
+    }
+    writeln!(out, "</g>")?;
+
+    writeln!(out, "</svg>")?;
+
+    Ok(out)
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Rasterises an SVG document into PNG bytes
+///
+/// A scale of 1.0 is 96 DPI
+pub fn png(svg: &str, scale: f32) -> Result<Vec<u8>> {
+    let mut tree = usvg::Tree::from_str(svg, &usvg::Options::default())?;
+    tree.convert_text(&fonts());
+
+    let width = (tree.size.width() * scale).ceil() as u32;
+    let height = (tree.size.height() * scale).ceil() as u32;
+
+    let mut pixmap = tiny_skia::Pixmap::new(width, height).ok_or(Error::msg(format!(
+        "Invalid image size {}x{}",
+        width, height
+    )))?;
+    pixmap.fill(tiny_skia::Color::WHITE);
+
+    resvg::Tree::from_usvg(&tree).render(
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    Ok(pixmap.encode_png()?)
+}
+
+//---------------------------------------------------------------------------//
+
+/// System fonts plus egui's bundled font, so text still renders on machines
+/// without any fonts installed
+fn fonts() -> fontdb::Database {
+    let mut fonts = fontdb::Database::new();
+    fonts.load_system_fonts();
+
+    for data in egui::FontDefinitions::default().font_data.into_values() {
+        fonts.load_font_data(data.font.into_owned());
+    }
+
+    fonts
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Whether `from → to` is an edge of the graph
+pub fn has_edge(graph: &WeightedGraph<String, i32>, from: &String, to: &String) -> bool {
+    graph.get_adj(from).contains(to)
+}
+
+//---------------------------------------------------------------------------//
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+///////////////////////////////////////////////////////////////////////////////
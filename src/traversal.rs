@@ -0,0 +1,573 @@
+///////////////////////////////////////////////////////////////////////////////
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use anyhow::{Error, Result};
+use cs_240_library::data_structures::graphs::IGraph;
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Which traversal to record
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Kind {
+    #[default]
+    Bfs,
+    Dfs,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Bfs => "Breadth-first",
+            Kind::Dfs => "Depth-first",
+        }
+    }
+
+    /// What the frontier is called
+    pub fn frontier(self) -> &'static str {
+        match self {
+            Kind::Bfs => "Queue",
+            Kind::Dfs => "Stack",
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// One thing the traversal did
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// Reached for the first time, from `parent`
+    Discover {
+        node: String,
+        parent: Option<String>,
+    },
+    /// Taken off the front of the queue
+    Dequeue(String),
+    /// Looked at the edge while going through the neighbours of `from`
+    Examine {
+        from: String,
+        to: String,
+        edge: EdgeKind,
+    },
+    /// All neighbours are done
+    Finish(String),
+}
+
+//---------------------------------------------------------------------------//
+
+/// Edge classification, as in CLRS
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// Led to a new node
+    Tree,
+    /// Goes back to an ancestor in the tree, or is a self-loop
+    Back,
+    /// Goes down to a descendant that is already finished
+    Forward,
+    /// Anything else
+    Cross,
+}
+
+//---------------------------------------------------------------------------//
+
+impl EdgeKind {
+    pub const ALL: [EdgeKind; 4] = [
+        EdgeKind::Tree,
+        EdgeKind::Back,
+        EdgeKind::Forward,
+        EdgeKind::Cross,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EdgeKind::Tree => "tree",
+            EdgeKind::Back => "back",
+            EdgeKind::Forward => "forward",
+            EdgeKind::Cross => "cross",
+        }
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+#[derive(Clone, Debug)]
+pub struct Step {
+    pub event: Event,
+    /// Queue from front to back, or stack from bottom to top, after the event
+    pub frontier: Vec<String>,
+}
+
+//---------------------------------------------------------------------------//
+
+/// Where a node is in the traversal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeState {
+    Unseen,
+    /// Waiting in the queue or on the stack
+    Frontier,
+    /// Off the frontier, neighbours not done yet
+    Visited,
+    Finished,
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+/// Every step of a traversal from one start node
+///
+/// Neighbours are taken in sorted order so runs are repeatable. The clock
+/// ticks on every discovery and finish, like in CLRS
+///
+/// In an undirected graph every edge is seen from both ends, and keeps the
+/// kind it got the first time
+#[derive(Clone, Debug)]
+pub struct Trace {
+    pub kind: Kind,
+    pub start: String,
+    pub steps: Vec<Step>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Trace {
+    //-------------------------------------------------------------------------//
+
+    pub fn new<G: IGraph<String>>(
+        graph: &G,
+        kind: Kind,
+        start: &str,
+        directed: bool,
+    ) -> Result<Self> {
+        let start = start.to_owned();
+        if !graph.contains(&start) {
+            return Err(Error::msg(format!("No node named \"{}\"", start)));
+        }
+
+        let steps = match kind {
+            Kind::Bfs => bfs(graph, &start, Classifier::new(directed)),
+            Kind::Dfs => dfs(graph, &start, Classifier::new(directed)),
+        };
+
+        Ok(Self { kind, start, steps })
+    }
+
+    //-------------------------------------------------------------------------//
+
+    /// Replays the first `count` steps
+    pub fn replay(&self, count: usize) -> Replay {
+        let mut res = Replay::default();
+
+        for step in &self.steps[..count.min(self.steps.len())] {
+            res.edge = None;
+
+            match &step.event {
+                Event::Discover { node, parent } => {
+                    res.time += 1;
+                    res.discovered.insert(node.clone(), res.time);
+                    if let Some(parent) = parent {
+                        res.parents.insert(node.clone(), parent.clone());
+                    }
+                    res.current = Some(node.clone());
+                }
+                Event::Dequeue(node) => res.current = Some(node.clone()),
+                Event::Examine { from, to, .. } => res.edge = Some((from.clone(), to.clone())),
+                Event::Finish(node) => {
+                    res.time += 1;
+                    res.finished.insert(node.clone(), res.time);
+                    res.current = Some(node.clone());
+                }
+            }
+
+            res.frontier = step.frontier.clone();
+        }
+
+        res
+    }
+
+    //-------------------------------------------------------------------------//
+}
+
+//---------------------------------------------------------------------------//
+
+/// State of a traversal part way through
+#[derive(Clone, Debug, Default)]
+pub struct Replay {
+    pub time: usize,
+    pub discovered: HashMap<String, usize>,
+    pub finished: HashMap<String, usize>,
+    /// Node each discovered node was reached from, the edges of the tree
+    pub parents: HashMap<String, String>,
+    pub frontier: Vec<String>,
+    /// Node the last step was about
+    pub current: Option<String>,
+    /// Edge the last step looked at
+    pub edge: Option<(String, String)>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Replay {
+    pub fn state(&self, node: &String) -> NodeState {
+        if self.finished.contains_key(node) {
+            NodeState::Finished
+        } else if self.frontier.contains(node) {
+            NodeState::Frontier
+        } else if self.discovered.contains_key(node) {
+            NodeState::Visited
+        } else {
+            NodeState::Unseen
+        }
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+fn sorted_adj<G: IGraph<String>>(graph: &G, node: &String) -> Vec<String> {
+    let mut adj = graph.get_adj(node);
+    adj.sort();
+    adj.dedup();
+    adj
+}
+
+//---------------------------------------------------------------------------//
+
+/// Remembers edge kinds, so undirected edges keep their first one
+struct Classifier {
+    directed: bool,
+    known: HashMap<(String, String), EdgeKind>,
+}
+
+//---------------------------------------------------------------------------//
+
+impl Classifier {
+    fn new(directed: bool) -> Self {
+        Self {
+            directed,
+            known: HashMap::new(),
+        }
+    }
+
+    fn classify(&mut self, from: &str, to: &str, kind: impl FnOnce() -> EdgeKind) -> EdgeKind {
+        let reverse = (to.to_owned(), from.to_owned());
+        let kind = match self.known.get(&reverse) {
+            Some(kind) if !self.directed => *kind,
+            _ => kind(),
+        };
+        self.known.insert((from.to_owned(), to.to_owned()), kind);
+        kind
+    }
+}
+
+//---------------------------------------------------------------------------//
+
+fn bfs<G: IGraph<String>>(graph: &G, start: &String, mut classifier: Classifier) -> Vec<Step> {
+    let mut steps = vec![];
+    let mut parents: HashMap<String, String> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    // directed breadth-first search has no forward edges
+    let is_ancestor = |parents: &HashMap<String, String>, node: &String, of: &String| {
+        let mut at = Some(of);
+        while let Some(current) = at {
+            if current == node {
+                return true;
+            }
+            at = parents.get(current);
+        }
+        false
+    };
+
+    seen.insert(start.clone());
+    queue.push_back(start.clone());
+    steps.push(Step {
+        event: Event::Discover {
+            node: start.clone(),
+            parent: None,
+        },
+        frontier: queue.iter().cloned().collect(),
+    });
+
+    while let Some(node) = queue.pop_front() {
+        steps.push(Step {
+            event: Event::Dequeue(node.clone()),
+            frontier: queue.iter().cloned().collect(),
+        });
+
+        for adj in sorted_adj(graph, &node) {
+            let new = !seen.contains(&adj);
+            let edge = classifier.classify(&node, &adj, || {
+                if new {
+                    EdgeKind::Tree
+                } else if is_ancestor(&parents, &adj, &node) {
+                    EdgeKind::Back
+                } else {
+                    EdgeKind::Cross
+                }
+            });
+
+            steps.push(Step {
+                event: Event::Examine {
+                    from: node.clone(),
+                    to: adj.clone(),
+                    edge,
+                },
+                frontier: queue.iter().cloned().collect(),
+            });
+
+            if new {
+                seen.insert(adj.clone());
+                parents.insert(adj.clone(), node.clone());
+                queue.push_back(adj.clone());
+                steps.push(Step {
+                    event: Event::Discover {
+                        node: adj,
+                        parent: Some(node.clone()),
+                    },
+                    frontier: queue.iter().cloned().collect(),
+                });
+            }
+        }
+
+        steps.push(Step {
+            event: Event::Finish(node),
+            frontier: queue.iter().cloned().collect(),
+        });
+    }
+
+    steps
+}
+
+//---------------------------------------------------------------------------//
+
+/// Iterative, so the stack shown is the real one: the path from the start to
+/// the node being explored
+fn dfs<G: IGraph<String>>(graph: &G, start: &String, mut classifier: Classifier) -> Vec<Step> {
+    let mut steps = vec![];
+    // discovery order, and which nodes are done
+    let mut discovered: HashMap<String, usize> = HashMap::new();
+    let mut finished = HashSet::new();
+    // each node with the neighbours it has left to look at
+    let mut stack: Vec<(String, std::vec::IntoIter<String>)> = vec![];
+
+    let frontier = |stack: &Vec<(String, std::vec::IntoIter<String>)>| {
+        stack.iter().map(|(node, _)| node.clone()).collect()
+    };
+
+    discovered.insert(start.clone(), 0);
+    stack.push((start.clone(), sorted_adj(graph, start).into_iter()));
+    steps.push(Step {
+        event: Event::Discover {
+            node: start.clone(),
+            parent: None,
+        },
+        frontier: frontier(&stack),
+    });
+
+    while let Some((node, rest)) = stack.last_mut() {
+        let node = node.clone();
+
+        match rest.next() {
+            Some(adj) => {
+                let new = !discovered.contains_key(&adj);
+                let edge = classifier.classify(&node, &adj, || {
+                    if new {
+                        EdgeKind::Tree
+                    } else if !finished.contains(&adj) {
+                        EdgeKind::Back
+                    } else if discovered[&node] < discovered[&adj] {
+                        EdgeKind::Forward
+                    } else {
+                        EdgeKind::Cross
+                    }
+                });
+
+                steps.push(Step {
+                    event: Event::Examine {
+                        from: node.clone(),
+                        to: adj.clone(),
+                        edge,
+                    },
+                    frontier: frontier(&stack),
+                });
+
+                if new {
+                    discovered.insert(adj.clone(), discovered.len());
+                    let next = sorted_adj(graph, &adj).into_iter();
+                    stack.push((adj.clone(), next));
+                    steps.push(Step {
+                        event: Event::Discover {
+                            node: adj,
+                            parent: Some(node),
+                        },
+                        frontier: frontier(&stack),
+                    });
+                }
+            }
+            None => {
+                stack.pop();
+                finished.insert(node.clone());
+                steps.push(Step {
+                    event: Event::Finish(node),
+                    frontier: frontier(&stack),
+                });
+            }
+        }
+    }
+
+    steps
+}
+
+///////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use cs_240_library::data_structures::graphs::{
+        directed_graph::DirectedGraph, undirected_graph::UndirectedGraph, IGraphEdgeMut, IGraphMut,
+    };
+
+    use super::*;
+
+    //-----------------------------------------------------------------------//
+
+    fn graph<G: IGraphMut<String> + IGraphEdgeMut<String>>(mut graph: G, edges: &[&str]) -> G {
+        for edge in edges {
+            let (from, to) = edge.split_once('-').unwrap();
+            graph.insert_node(from.to_owned());
+            graph.insert_node(to.to_owned());
+            graph.insert_edge(from.to_owned(), to.to_owned());
+        }
+        graph
+    }
+
+    //-----------------------------------------------------------------------//
+
+    fn kinds(trace: &Trace) -> Vec<(String, EdgeKind)> {
+        trace
+            .steps
+            .iter()
+            .filter_map(|step| match &step.event {
+                Event::Examine { from, to, edge } => Some((format!("{}-{}", from, to), *edge)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    //-----------------------------------------------------------------------//
+
+    fn kind(edge: &str, kind: EdgeKind) -> (String, EdgeKind) {
+        (edge.to_owned(), kind)
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn dfs_finds_every_edge_kind() {
+        let graph = graph(
+            DirectedGraph::new(),
+            &["a-b", "a-c", "a-d", "b-d", "c-b", "d-a"],
+        );
+        let trace = Trace::new(&graph, Kind::Dfs, "a", true).unwrap();
+
+        assert_eq!(
+            kinds(&trace),
+            [
+                kind("a-b", EdgeKind::Tree),
+                kind("b-d", EdgeKind::Tree),
+                kind("d-a", EdgeKind::Back),
+                kind("a-c", EdgeKind::Tree),
+                kind("c-b", EdgeKind::Cross),
+                kind("a-d", EdgeKind::Forward),
+            ]
+        );
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn bfs_has_no_forward_edges() {
+        let graph = graph(DirectedGraph::new(), &["a-b", "a-c", "b-c", "c-a"]);
+        let trace = Trace::new(&graph, Kind::Bfs, "a", true).unwrap();
+
+        assert_eq!(
+            kinds(&trace),
+            [
+                kind("a-b", EdgeKind::Tree),
+                kind("a-c", EdgeKind::Tree),
+                kind("b-c", EdgeKind::Cross),
+                kind("c-a", EdgeKind::Back),
+            ]
+        );
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn undirected_edges_keep_their_first_kind() {
+        let graph = graph(UndirectedGraph::new(), &["a-b"]);
+        let trace = Trace::new(&graph, Kind::Dfs, "a", false).unwrap();
+
+        assert_eq!(
+            kinds(&trace),
+            [kind("a-b", EdgeKind::Tree), kind("b-a", EdgeKind::Tree)]
+        );
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn missing_start() {
+        let graph = graph(DirectedGraph::new(), &["a-b"]);
+        assert!(Trace::new(&graph, Kind::Bfs, "x", true).is_err());
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn replay_part_way() {
+        let graph = graph(DirectedGraph::new(), &["a-b", "b-c"]);
+        let trace = Trace::new(&graph, Kind::Dfs, "a", true).unwrap();
+        let node = |name: &str| name.to_owned();
+
+        let start = trace.replay(0);
+        assert_eq!(start.time, 0);
+        assert_eq!(start.state(&node("a")), NodeState::Unseen);
+
+        // discover a, examine a-b
+        let replay = trace.replay(2);
+        assert_eq!(replay.state(&node("a")), NodeState::Frontier);
+        assert_eq!(replay.state(&node("b")), NodeState::Unseen);
+        assert_eq!(replay.edge, Some((node("a"), node("b"))));
+        assert_eq!(replay.current, Some(node("a")));
+
+        // discover b
+        let replay = trace.replay(3);
+        assert_eq!(replay.edge, None);
+        assert_eq!(replay.current, Some(node("b")));
+        assert_eq!(replay.parents.get("b"), Some(&node("a")));
+        assert_eq!(replay.frontier, ["a", "b"]);
+    }
+
+    //-----------------------------------------------------------------------//
+
+    #[test]
+    fn replay_to_the_end() {
+        let graph = graph(DirectedGraph::new(), &["a-b", "b-c"]);
+        let trace = Trace::new(&graph, Kind::Dfs, "a", true).unwrap();
+
+        // asking for more steps than there are is the same as all of them
+        let replay = trace.replay(usize::MAX);
+
+        assert_eq!(replay.time, 6);
+        assert!(replay.frontier.is_empty());
+        for (node, discovered, finished) in [("a", 1, 6), ("b", 2, 5), ("c", 3, 4)] {
+            assert_eq!(replay.discovered[node], discovered);
+            assert_eq!(replay.finished[node], finished);
+            assert_eq!(replay.state(&node.to_owned()), NodeState::Finished);
+        }
+    }
+
+    //-----------------------------------------------------------------------//
+}